@@ -1,9 +1,9 @@
-use bevy::prelude::*;
+use bevy::{input::gamepad::GamepadInput, prelude::*};
 use bevy_hid::{HidPlugin, buffers::HidBuffer};
 
 fn main() {
     App::new()
-        .add_plugins((DefaultPlugins, HidPlugin))
+        .add_plugins((DefaultPlugins, HidPlugin::default()))
         .add_systems(Update, check_devices)
         .run();
 }
@@ -11,14 +11,8 @@ fn main() {
 fn check_devices(gamepads: Query<&Gamepad, With<HidBuffer>>) {
     for gamepad in gamepads.iter() {
         for axis in gamepad.get_analog_axes() {
-            match axis {
-                bevy::input::gamepad::GamepadInput::Axis(gamepad_axis) => match gamepad_axis {
-                    GamepadAxis::Other(_) => {
-                        println!("Axis: {:?}", gamepad.get_unclamped(*axis))
-                    }
-                    _ => {}
-                },
-                _ => {}
+            if let GamepadInput::Axis(GamepadAxis::Other(_)) = axis {
+                println!("Axis: {:?}", gamepad.get_unclamped(*axis))
             }
         }
     }
@@ -1,17 +1,16 @@
 use bevy_hid::{
+    assets::ron_options,
     buffers::BufferMap,
     device::{DeviceAsset, DeviceId, DeviceMap},
-    input::ButtonQuery,
 };
 use hidapi::HidApi;
-use ron::de::from_reader;
 use std::{collections::HashMap, fs::File, thread::sleep, time::Duration};
 
 const SLEEP_TIME: u64 = 10;
 
 fn load_device(path: &str) -> Option<DeviceMap> {
     let f = File::open(path).ok()?;
-    let asset: DeviceAsset = from_reader(f).ok()?;
+    let asset: DeviceAsset = ron_options().from_reader(f).ok()?;
     Some(asset.mappings().clone())
 }
 
@@ -87,14 +86,8 @@ fn main() {
 
                 for button in buttons.iter() {
                     if let Some(ptr) = device_map.buttons.get(button) {
-                        let pressed = match ptr.1 {
-                            ButtonQuery::Bit(mask) => (new & mask) != 0,
-                            ButtonQuery::Eq(val) => new == val,
-                        };
-                        let was_pressed = match ptr.1 {
-                            ButtonQuery::Bit(mask) => (last & mask) != 0,
-                            ButtonQuery::Eq(val) => last == val,
-                        };
+                        let pressed = ptr.pressed(buf_new);
+                        let was_pressed = ptr.pressed(&buf_last);
                         if pressed != was_pressed {
                             println!(
                                 "Button {:?} ({:?}): {}",
@@ -1,23 +1,41 @@
 use bevy::{
-    asset::{AssetLoader, LoadContext, LoadedFolder, io::Reader},
+    asset::{
+        AssetLoader, AssetPath, LoadContext, LoadedFolder, ParseAssetPathError,
+        ReadAssetBytesError, io::Reader,
+    },
     prelude::*,
 };
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
+    bindings::{Binding, BindingLabel},
     buffers::BufferMap,
-    device::{DeviceMap, DeviceAsset},
+    device::{DeviceAsset, DeviceMap},
+    formats::{AssetFormat, Ron},
+    input::AxisEncoding,
+    migration::{SCHEMA_VERSION, migrate_from, migrate_path},
+    validation::{REPORT_SIZE, ValidationError, ValidationIssue},
 };
+use std::marker::PhantomData;
 
 #[derive(Clone, Asset, Reflect)]
 pub struct HidAsset {
     pub(crate) name: String,
     pub(crate) input_mapping: DeviceMap,
     pub(crate) buffer_map: BufferMap,
+    pub(crate) locale: Option<String>,
 }
 
-#[derive(Default, Reflect)]
-pub(crate) struct HidAssetLoader;
+/// Loads device assets in any of the supported formats. Every format goes through the same migration and base resolution.
+#[derive(TypePath)]
+pub(crate) struct HidAssetLoader<F = Ron>(PhantomData<fn() -> F>);
+
+impl<F> Default for HidAssetLoader<F> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
 
 #[derive(Error, Debug)]
 pub enum HidAssetLoadError {
@@ -25,36 +43,274 @@ pub enum HidAssetLoadError {
     Io(#[from] std::io::Error),
     #[error("Could not deserialize RON: {0}")]
     Ron(#[from] ron::de::SpannedError),
+    #[cfg(feature = "json")]
+    #[error("Could not deserialize JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "toml")]
+    #[error("Could not deserialize TOML: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("Could not resolve base asset path: {0}")]
+    BasePath(#[from] ParseAssetPathError),
+    #[error("Could not load base asset: {0}")]
+    Base(#[from] ReadAssetBytesError),
+    #[error("Base asset {0} inherits from itself")]
+    BaseCycle(String),
+    #[error("Unsupported schema version {0}, the newest supported version is {max}", max = SCHEMA_VERSION)]
+    UnsupportedVersion(u32),
+    #[error("Invalid mappings: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    Invalid(Vec<ValidationIssue<ValidationError>>),
+}
+
+#[derive(Error, Debug)]
+pub enum HidAssetSaveError {
+    #[error("Could not save asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not serialize RON: {0}")]
+    Ron(#[from] ron::Error),
+}
+
+/// Per-asset loader settings, set through `.meta` files. Settings left out of a `.meta` file keep their defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HidAssetSettings {
+    /// How validation errors are handled.
+    pub validation: ValidationMode,
+    /// The encoding used by axes that don't declare one.
+    pub axis_encoding: Option<AxisEncoding>,
+    /// The deadzone given to axes that don't declare one.
+    pub deadzone: Option<f32>,
+    /// The length in bytes of the device's reports. Bindings are validated against this, rather than the
+    /// [`REPORT_SIZE`] buffer reports are read into.
+    pub report_size: Option<usize>,
+    /// The locale labels are shown in while [`HidLocale`](crate::resources::HidLocale) is unset. The labels
+    /// themselves are kept as written, so a locale chosen at runtime still takes over.
+    pub locale: Option<String>,
+}
+
+/// How the loader treats mappings that fail validation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValidationMode {
+    /// Assets with validation errors fail to load.
+    #[default]
+    Strict,
+    /// Validation errors are logged, and the asset loads anyway.
+    Lenient,
+}
+
+impl HidAssetSettings {
+    /// Fills in the defaults of a loaded mapping.
+    pub(crate) fn apply(&self, mappings: &mut DeviceMap) {
+        for axis in mappings.axes.values_mut() {
+            axis.encoding = axis.encoding.or(self.axis_encoding);
+            axis.deadzone = axis.deadzone.or(self.deadzone);
+        }
+    }
+}
+
+/// The asset folders device mappings are loaded from, in order of precedence (last wins).
+#[derive(Resource, Clone)]
+pub(crate) struct HidAssetFolders(pub(crate) Vec<String>);
+
+impl HidAssetFolders {
+    /// The index of the folder an asset was loaded from, or of the folder itself. Nested folders resolve to the
+    /// last one listed.
+    pub(crate) fn folder_of(&self, path: &AssetPath) -> Option<usize> {
+        self.0.iter().rposition(|folder| {
+            let folder = AssetPath::parse(folder);
+            folder.source() == path.source() && path.path().starts_with(folder.path())
+        })
+    }
 }
 
 #[derive(Resource, Clone)]
 pub(crate) struct TemporaryDeviceAssets {
-    pub(crate) device_assets: Handle<LoadedFolder>,
+    pub(crate) device_assets: Vec<Handle<LoadedFolder>>,
 }
 
 impl HidAsset {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// The locale labels are shown in when no [`HidLocale`](crate::resources::HidLocale) is set.
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    /// Gets the display label for one of the device's inputs, e.g. "Pinky switch" rather than `Other(2)`.
+    pub fn label_for(&self, binding: impl Into<Binding>) -> Option<&BindingLabel> {
+        self.input_mapping.label_for(binding)
+    }
+}
+
+impl From<&DeviceAsset> for HidAsset {
+    fn from(device: &DeviceAsset) -> Self {
+        HidAsset {
+            name: device.name().to_string(),
+            input_mapping: device.mappings().clone(),
+            buffer_map: BufferMap::from(device.mappings()),
+            locale: device.locale.clone(),
+        }
+    }
 }
 
-impl AssetLoader for HidAssetLoader {
+/// The RON options used when reading device assets. `implicit_some` keeps optional mapping fields terse.
+pub fn ron_options() -> ron::Options {
+    ron::Options::default().with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+}
+
+/// The layout used when writing device assets. Bindings are kept to a line each, like the hand-written assets,
+/// so there is room to annotate them with comments.
+pub fn pretty_config() -> ron::ser::PrettyConfig {
+    ron::ser::PrettyConfig::new()
+        .depth_limit(3)
+        .indentor("    ")
+        .separate_tuple_members(false)
+}
+
+impl<F: AssetFormat> AssetLoader for HidAssetLoader<F> {
     type Asset = DeviceAsset;
-    type Settings = ();
+    type Settings = HidAssetSettings;
     type Error = HidAssetLoadError;
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &(),
-        _load_context: &mut LoadContext<'_>,
+        settings: &HidAssetSettings,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let custom_asset = ron::de::from_bytes::<DeviceAsset>(&bytes)?;
+        let (mut custom_asset, warnings) = migrate_from::<F>(&bytes)?;
+        for warning in warnings {
+            warn!("{}: {}", load_context.path(), warning);
+        }
+        // Bases are read directly rather than loaded as assets, so the chain can be checked for cycles.
+        let mut chain = vec![load_context.path().clone_owned()];
+        let mut next = custom_asset.base.clone();
+        while let Some(base) = next {
+            let path: AssetPath<'static> = chain[chain.len() - 1].resolve_embed(&base)?;
+            if chain.contains(&path) {
+                return Err(HidAssetLoadError::BaseCycle(path.to_string()));
+            }
+            let bytes = load_context.read_asset_bytes(&path).await?;
+            let (base, _) = migrate_path(path.path(), &bytes)?;
+            custom_asset.mappings_mut().inherit(base.mappings());
+            next = base.base;
+            chain.push(path);
+        }
+        settings.apply(custom_asset.mappings_mut());
+        custom_asset.locale = settings.locale.clone();
+
+        let report_size = settings.report_size.unwrap_or(REPORT_SIZE);
+        let report = custom_asset.validate_report_size(report_size);
+        let report = match std::str::from_utf8(&bytes) {
+            Ok(source) => report.with_spans(source),
+            Err(_) => report,
+        };
+        for warning in &report.warnings {
+            warn!("{}: {}", load_context.path(), warning);
+        }
+        match settings.validation {
+            ValidationMode::Strict if !report.is_valid() => {
+                return Err(HidAssetLoadError::Invalid(report.errors));
+            }
+            ValidationMode::Strict => {}
+            ValidationMode::Lenient => {
+                for error in &report.errors {
+                    warn!("{}: {}", load_context.path(), error);
+                }
+            }
+        }
         Ok(custom_asset)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["hid.ron"]
+        F::EXTENSIONS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::load_raw_devices;
+    use bevy::prelude::GamepadButton;
+
+    #[test]
+    fn test_settings_apply() {
+        let mut throttle = load_raw_devices()
+            .into_iter()
+            .find(|device| device.name() == "TWCS Throttle (HID)")
+            .unwrap();
+        let settings = HidAssetSettings {
+            axis_encoding: Some(AxisEncoding::Unsigned),
+            deadzone: Some(0.1),
+            ..Default::default()
+        };
+        settings.apply(throttle.mappings_mut());
+
+        let mappings = throttle.mappings();
+        assert!(mappings.axes.values().all(|axis| axis.deadzone.is_some()));
+        assert!(
+            mappings
+                .axes
+                .values()
+                .all(|axis| axis.encoding == Some(AxisEncoding::Unsigned))
+        );
+        // Labels are left for `HidLocale` to translate.
+        let label = mappings
+            .label_for(Binding::Button(GamepadButton::Other(2)))
+            .unwrap();
+        assert_eq!(label.short_or_label(), "Pinky");
+
+        let partial: HidAssetSettings = ron_options().from_str("(deadzone: Some(0.05))").unwrap();
+        assert_eq!(partial.validation, ValidationMode::Strict);
+        assert_eq!(partial.deadzone, Some(0.05));
+    }
+
+    #[test]
+    fn test_base_cycle() {
+        use bevy::asset::{AssetPlugin, LoadState};
+
+        let dir = crate::util::temp_dir("base_cycle");
+        let asset = |name: &str, base: &str| {
+            let content = format!(
+                r#"(version: 2, name: "{name}", ids: [], base: "{base}", mappings: (buttons: {{}}, axes: {{}}))"#
+            );
+            std::fs::write(dir.join(format!("{name}.hid.ron")), content).unwrap();
+        };
+        asset("a", "b.hid.ron");
+        asset("b", "a.hid.ron");
+        asset("c", "c.hid.ron");
+
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin {
+                file_path: dir.to_string_lossy().to_string(),
+                ..Default::default()
+            },
+        ))
+        .init_asset::<DeviceAsset>()
+        .register_asset_loader(HidAssetLoader::<Ron>::default());
+
+        let server = app.world().resource::<AssetServer>().clone();
+        let handles = [
+            server.load::<DeviceAsset>("a.hid.ron"),
+            server.load("c.hid.ron"),
+        ];
+        for handle in handles {
+            let state = loop {
+                app.update();
+                match server.load_state(&handle) {
+                    LoadState::Loading | LoadState::NotLoaded => std::thread::yield_now(),
+                    state => break state,
+                }
+            };
+            let LoadState::Failed(error) = state else {
+                panic!("{handle:?} loaded despite its base cycle");
+            };
+            assert!(error.to_string().contains("inherits from itself"));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
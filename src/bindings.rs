@@ -1,15 +1,87 @@
 use bevy::{
-    input::gamepad::{GamepadAxis, GamepadButton}, reflect::Reflect
+    input::gamepad::{GamepadAxis, GamepadButton, GamepadInput},
+    reflect::Reflect,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::serialization::sorted_map;
 
 /// The main identifier of an input type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Reflect)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
 pub enum Binding {
     Button(GamepadButton),
     Axis(GamepadAxis),
 }
 
+/// Display metadata for a binding, for use in rebinding screens and prompts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub struct BindingLabel {
+    /// The full name of the input, e.g. "Pinky switch".
+    pub label: String,
+    /// A shorter name for cramped layouts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short: Option<String>,
+    /// A key identifying the icon or glyph used to draw the input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glyph: Option<String>,
+    /// Translated names, keyed by locale (e.g. "fr" or "pt-BR").
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "sorted_map"
+    )]
+    pub translations: HashMap<String, LocalizedLabel>,
+}
+
+/// A translated name for a binding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub struct LocalizedLabel {
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short: Option<String>,
+}
+
+impl BindingLabel {
+    /// The short name if there is one, otherwise the full name.
+    pub fn short_or_label(&self) -> &str {
+        self.short.as_deref().unwrap_or(&self.label)
+    }
+
+    /// Finds the translation for a locale, falling back from a region (e.g. "pt-BR") to its language ("pt").
+    pub fn translation(&self, locale: &str) -> Option<&LocalizedLabel> {
+        self.translations.get(locale).or_else(|| {
+            let (language, _) = locale.split_once(['-', '_'])?;
+            self.translations.get(language)
+        })
+    }
+
+    /// The full name in the given locale, or the untranslated name if there is no translation.
+    pub fn label_in(&self, locale: Option<&str>) -> &str {
+        match locale.and_then(|locale| self.translation(locale)) {
+            Some(translation) => &translation.label,
+            None => &self.label,
+        }
+    }
+
+    /// The short name in the given locale, falling back to the full name and then the untranslated names.
+    pub fn short_in(&self, locale: Option<&str>) -> &str {
+        match locale.and_then(|locale| self.translation(locale)) {
+            Some(translation) => translation.short.as_deref().unwrap_or(&translation.label),
+            None => self.short_or_label(),
+        }
+    }
+}
+
+impl From<GamepadInput> for Binding {
+    fn from(input: GamepadInput) -> Self {
+        match input {
+            GamepadInput::Axis(axis) => Self::Axis(axis),
+            GamepadInput::Button(button) => Self::Button(button),
+        }
+    }
+}
+
 impl From<GamepadButton> for Binding {
     fn from(button: GamepadButton) -> Self {
         Self::Button(button)
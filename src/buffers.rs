@@ -1,12 +1,34 @@
 use std::collections::{HashMap, HashSet};
 
-use bevy::{ecs::component::Component, prelude::GamepadButton, reflect::Reflect};
+use bevy::{
+    ecs::component::Component,
+    prelude::{GamepadAxis, GamepadButton},
+    reflect::Reflect,
+};
 
 use crate::device::DeviceMap;
 
-/// Stores the last buffer state of a hid. Used for internal caching.
+/// Stores the last buffer state of a hid, and whether a report has been read into it since it was reset.
+/// Used for internal caching.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Component)]
-pub struct HidBuffer(pub(crate) [u8; 256]);
+pub struct HidBuffer(pub(crate) [u8; 256], pub(crate) bool);
+
+/// Stores the current value of each button driven axis on a hid, as these can ramp over several frames.
+#[derive(Debug, Clone, Default, PartialEq, Reflect, Component)]
+pub struct ButtonAxisState(pub(crate) HashMap<GamepadAxis, f32>);
+
+/// Counts the increment and decrement pulses still to be sent for a hid's relative axes. Each button is pulsed once
+/// per frame, so a fast spin that moves several steps in one report is not collapsed into a single press.
+#[derive(Debug, Clone, Default, PartialEq, Reflect, Component)]
+pub struct PulseState(pub(crate) HashMap<GamepadButton, u32>);
+
+/// Tracks chords on a hid: when each button was pressed, which chords are held and which buttons they are muting.
+#[derive(Debug, Clone, Default, PartialEq, Reflect, Component)]
+pub struct ChordState {
+    pub(crate) pressed_at: HashMap<GamepadButton, f32>,
+    pub(crate) active: HashSet<GamepadButton>,
+    pub(crate) suppressed: HashSet<GamepadButton>,
+}
 
 /// A buffer map is essentially an inverted DeviceMap which indexes by buffer indices rather than action types.
 /// This minimizes buffer lookups and allows for quick parsing of device buffers.
@@ -15,7 +37,108 @@ pub struct BufferMap(HashMap<u8, Vec<GamepadButton>>);
 
 impl Default for HidBuffer {
     fn default() -> Self {
-        Self([0; 256])
+        Self([0; 256], false)
+    }
+}
+
+impl ButtonAxisState {
+    /// Gets the current value of a button driven axis.
+    pub fn get(&self, axis: GamepadAxis) -> f32 {
+        self.0.get(&axis).copied().unwrap_or_default()
+    }
+}
+
+impl PulseState {
+    /// Queues a number of pulses for a button.
+    pub(crate) fn queue(&mut self, button: GamepadButton, steps: u32) {
+        *self.0.entry(button).or_default() += steps;
+    }
+
+    /// Takes one queued pulse from every button that has any left.
+    pub(crate) fn next(&mut self) -> Vec<GamepadButton> {
+        self.0.retain(|_, steps| *steps > 0);
+        self.0
+            .iter_mut()
+            .map(|(button, steps)| {
+                *steps -= 1;
+                *button
+            })
+            .collect()
+    }
+}
+
+impl ChordState {
+    /// Checks if a chord's virtual button is currently held.
+    pub fn is_active(&self, chord: GamepadButton) -> bool {
+        self.active.contains(&chord)
+    }
+
+    /// Updates the chords for a new report, returning the button values that should be written.
+    /// `changed` holds the base buttons whose value may have changed since the last report.
+    pub(crate) fn update(
+        &mut self,
+        map: &DeviceMap,
+        changed: &HashSet<GamepadButton>,
+        buf_new: &[u8],
+        buf_last: &[u8],
+        now: f32,
+    ) -> Vec<(GamepadButton, f32)> {
+        for button in changed {
+            let Some(ptr) = map.buttons.get(button) else {
+                continue;
+            };
+            match (ptr.pressed(buf_new), ptr.pressed(buf_last)) {
+                (true, false) => {
+                    self.pressed_at.insert(*button, now);
+                }
+                (false, true) => {
+                    self.pressed_at.remove(button);
+                }
+                _ => {}
+            }
+        }
+
+        let layer_last = map.active_layer(buf_last);
+        let mut events = Vec::new();
+        for (chord_button, chord) in &map.chords {
+            // A chord without buttons is never held, rather than held forever.
+            let held = !chord.buttons.is_empty()
+                && chord.buttons.iter().all(|button| {
+                    map.buttons
+                        .get(button)
+                        .is_some_and(|ptr| ptr.pressed(buf_new))
+                });
+            let active = self.active.contains(chord_button);
+            if held && !active && chord.within_window(&self.pressed_at) {
+                self.active.insert(*chord_button);
+                events.push((*chord_button, 1.0));
+                if !chord.suppress {
+                    continue;
+                }
+                for button in &chord.buttons {
+                    // Buttons held before this report have already been written, so they need releasing.
+                    let written = map.buttons[button].value(buf_last) != 0.0;
+                    if self.suppressed.insert(*button) && written {
+                        events.push((map.resolve(*button, layer_last), 0.0));
+                    }
+                }
+            } else if !held && active {
+                self.active.remove(chord_button);
+                events.push((*chord_button, 0.0));
+            }
+        }
+        events
+    }
+
+    /// Checks if a button is muted by a chord, unmuting it once it has been let go.
+    pub(crate) fn suppress(&mut self, button: GamepadButton, pressed: bool) -> bool {
+        if !self.suppressed.contains(&button) {
+            return false;
+        }
+        if !pressed {
+            self.suppressed.remove(&button);
+        }
+        true
     }
 }
 
@@ -24,6 +147,20 @@ impl BufferMap {
         self.0.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Collects the buttons that read from any byte that differs between two buffers.
+    /// Buttons spanning several bytes are only listed once.
+    pub fn changed(&self, buf_new: &[u8], buf_last: &[u8]) -> HashSet<GamepadButton> {
+        self.0
+            .iter()
+            .filter(|(i, _)| buf_new[**i as usize] != buf_last[**i as usize])
+            .flat_map(|(_, buttons)| buttons.iter().copied())
+            .collect()
+    }
+
     pub fn iter(&self) -> std::collections::hash_map::Iter<'_, u8, Vec<GamepadButton>> {
         self.0.iter()
     }
@@ -33,14 +170,10 @@ impl From<&DeviceMap> for BufferMap {
     fn from(device_map: &DeviceMap) -> Self {
         let mut buffer_map: HashMap<u8, HashSet<GamepadButton>> = HashMap::new();
 
-        // only buttons
+        // only buttons, indexed by every byte they read from
         for (button, ptr) in &device_map.buttons {
-            if let Some(buf) = buffer_map.get_mut(&ptr.0) {
-                buf.insert(*button);
-            } else {
-                let mut hashset = HashSet::new();
-                hashset.insert(*button);
-                buffer_map.insert(ptr.0, hashset);
+            for index in ptr.indices() {
+                buffer_map.entry(index).or_default().insert(*button);
             }
         }
         BufferMap(
@@ -51,3 +184,84 @@ impl From<&DeviceMap> for BufferMap {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        device::Chord,
+        input::{ButtonPointer, ButtonQuery},
+    };
+
+    #[test]
+    fn test_chord_suppression() {
+        let buttons = HashMap::from([
+            (GamepadButton::South, ButtonPointer(0, ButtonQuery::Bit(1))),
+            (GamepadButton::East, ButtonPointer(0, ButtonQuery::Bit(2))),
+        ]);
+        let chord = Chord {
+            buttons: vec![GamepadButton::South, GamepadButton::East],
+            window: Some(0.5),
+            suppress: true,
+        };
+        let map = DeviceMap::new(buttons, HashMap::new())
+            .with_chords(HashMap::from([(GamepadButton::Other(50), chord)]));
+        let changed = HashSet::from([GamepadButton::South, GamepadButton::East]);
+        let mut state = ChordState::default();
+
+        assert!(state.update(&map, &changed, &[1], &[0], 0.0).is_empty());
+        let events = state.update(&map, &changed, &[3], &[1], 0.25);
+        assert!(events.contains(&(GamepadButton::Other(50), 1.0)));
+        assert!(events.contains(&(GamepadButton::South, 0.0)));
+        assert_eq!(events.len(), 2);
+        assert!(state.suppress(GamepadButton::East, true));
+
+        let events = state.update(&map, &changed, &[0], &[3], 1.0);
+        assert_eq!(events, vec![(GamepadButton::Other(50), 0.0)]);
+        assert!(state.suppress(GamepadButton::South, false));
+        assert!(!state.suppress(GamepadButton::South, true));
+    }
+
+    #[test]
+    fn test_chord_window() {
+        let buttons = HashMap::from([
+            (GamepadButton::South, ButtonPointer(0, ButtonQuery::Bit(1))),
+            (GamepadButton::East, ButtonPointer(0, ButtonQuery::Bit(2))),
+        ]);
+        let chord = Chord {
+            buttons: vec![GamepadButton::South, GamepadButton::East],
+            window: Some(0.5),
+            suppress: false,
+        };
+        let map = DeviceMap::new(buttons, HashMap::new())
+            .with_chords(HashMap::from([(GamepadButton::Other(50), chord)]));
+        let changed = HashSet::from([GamepadButton::South, GamepadButton::East]);
+        let mut state = ChordState::default();
+
+        state.update(&map, &changed, &[1], &[0], 0.0);
+        assert!(state.update(&map, &changed, &[3], &[1], 2.0).is_empty());
+        assert!(!state.is_active(GamepadButton::Other(50)));
+
+        let empty = Chord {
+            buttons: Vec::new(),
+            window: None,
+            suppress: false,
+        };
+        let map = map.with_chords(HashMap::from([(GamepadButton::Other(1), empty)]));
+        let mut state = ChordState::default();
+        assert!(state.update(&map, &changed, &[3], &[0], 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_pulse_queue() {
+        let mut pulses = PulseState::default();
+        pulses.queue(GamepadButton::DPadUp, 3);
+        pulses.queue(GamepadButton::DPadDown, 1);
+        let mut first = pulses.next();
+        first.sort_by_key(|button| format!("{button:?}"));
+        assert_eq!(first, [GamepadButton::DPadDown, GamepadButton::DPadUp]);
+        assert_eq!(pulses.next(), [GamepadButton::DPadUp]);
+        assert_eq!(pulses.next(), [GamepadButton::DPadUp]);
+        assert!(pulses.next().is_empty());
+    }
+}
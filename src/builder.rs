@@ -0,0 +1,387 @@
+use bevy::prelude::{GamepadAxis, GamepadButton};
+use thiserror::Error;
+
+use crate::{
+    bindings::{Binding, BindingLabel},
+    device::{Chord, DeviceMap, ShiftLayer},
+    input::{
+        AxisCalibration, AxisEncoding, AxisPointer, AxisZone, ButtonAxisPointer, ButtonPointer,
+        ButtonQuery, RelativePointer, ResponseCurve, StickPair, SwitchPointer,
+    },
+    validation::{ValidationError, ValidationIssue},
+};
+
+/// Builds a [`DeviceMap`] from code, e.g.
+/// `DeviceMap::builder().button(GamepadButton::South).bit(17, 0).axis(GamepadAxis::LeftZ).absolute().bytes(5, 6).build()`.
+/// Bindings are checked when the map is built.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceMapBuilder {
+    map: DeviceMap,
+    errors: Vec<DeviceMapError>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum DeviceMapError {
+    #[error("{0:?} is bound more than once")]
+    Duplicate(Binding),
+    #[error("switch {0:?} is added more than once")]
+    DuplicateSwitch(String),
+    #[error("layer {0:?} is added more than once")]
+    DuplicateLayer(String),
+    #[error("{0:?} is labelled more than once")]
+    DuplicateLabel(Binding),
+    #[error("{0:?} reads bit {1}, but a byte only has bits 0 to 7")]
+    InvalidBit(GamepadButton, u8),
+    #[error("{0}")]
+    Invalid(ValidationIssue<ValidationError>),
+}
+
+/// A button being added to a [`DeviceMapBuilder`]. Choose how the button is read to add it.
+#[derive(Debug, Clone)]
+pub struct ButtonBuilder {
+    builder: DeviceMapBuilder,
+    button: GamepadButton,
+}
+
+/// An axis being added to a [`DeviceMapBuilder`]. Set any options, then give the bytes it is read from to add it.
+#[derive(Debug, Clone)]
+pub struct AxisBuilder {
+    builder: DeviceMapBuilder,
+    axis: GamepadAxis,
+    pointer: AxisPointer,
+}
+
+impl DeviceMap {
+    pub fn builder() -> DeviceMapBuilder {
+        DeviceMapBuilder::default()
+    }
+}
+
+impl DeviceMapBuilder {
+    pub fn button(self, button: GamepadButton) -> ButtonBuilder {
+        ButtonBuilder {
+            builder: self,
+            button,
+        }
+    }
+
+    /// Starts an axis. Axes default to 256 octaves, which suits a full 16 bit value.
+    pub fn axis(self, axis: GamepadAxis) -> AxisBuilder {
+        AxisBuilder {
+            builder: self,
+            axis,
+            pointer: AxisPointer {
+                octaves: 256,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Adds an axis driven by a pair of buttons.
+    pub fn button_axis(mut self, axis: GamepadAxis, pointer: ButtonAxisPointer) -> Self {
+        if self.map.button_axes.insert(axis, pointer).is_some() {
+            self.errors.push(DeviceMapError::Duplicate(axis.into()));
+        }
+        self
+    }
+
+    pub fn relative_axis(mut self, axis: GamepadAxis, pointer: RelativePointer) -> Self {
+        if self.map.relative_axes.insert(axis, pointer).is_some() {
+            self.errors.push(DeviceMapError::Duplicate(axis.into()));
+        }
+        self
+    }
+
+    pub fn switch(mut self, name: impl Into<String>, pointer: SwitchPointer) -> Self {
+        let name = name.into();
+        if self.map.switches.insert(name.clone(), pointer).is_some() {
+            self.errors.push(DeviceMapError::DuplicateSwitch(name));
+        }
+        self
+    }
+
+    /// Pairs two axes into a stick. Each axis can only belong to one stick.
+    pub fn stick(mut self, stick: StickPair) -> Self {
+        for axis in [stick.x, stick.y] {
+            if self.map.sticks.iter().any(|other| other.contains(axis)) {
+                self.errors.push(DeviceMapError::Duplicate(axis.into()));
+            }
+        }
+        self.map.sticks.push(stick);
+        self
+    }
+
+    pub fn layer(mut self, layer: ShiftLayer) -> Self {
+        if self.map.layers.iter().any(|other| other.name == layer.name) {
+            self.errors
+                .push(DeviceMapError::DuplicateLayer(layer.name.clone()));
+        }
+        self.map.layers.push(layer);
+        self
+    }
+
+    pub fn chord(mut self, button: GamepadButton, chord: Chord) -> Self {
+        if self.map.chords.insert(button, chord).is_some() {
+            self.errors.push(DeviceMapError::Duplicate(button.into()));
+        }
+        self
+    }
+
+    pub fn label(mut self, binding: impl Into<Binding>, label: BindingLabel) -> Self {
+        let binding = binding.into();
+        if self.map.labels.insert(binding, label).is_some() {
+            self.errors.push(DeviceMapError::DuplicateLabel(binding));
+        }
+        self
+    }
+
+    /// Validates the bindings and returns the finished map, or the first error found.
+    /// Validation warnings are not treated as errors.
+    pub fn build(self) -> Result<DeviceMap, DeviceMapError> {
+        if let Some(error) = self.errors.into_iter().next() {
+            return Err(error);
+        }
+        match self.map.validate().errors.into_iter().next() {
+            Some(issue) => Err(DeviceMapError::Invalid(issue)),
+            None => Ok(self.map),
+        }
+    }
+
+    fn add_button(mut self, button: GamepadButton, pointer: ButtonPointer) -> Self {
+        if self.map.buttons.insert(button, pointer).is_some() {
+            self.errors.push(DeviceMapError::Duplicate(button.into()));
+        }
+        self
+    }
+}
+
+impl ButtonBuilder {
+    /// Pressed while a single bit (0 to 7) of a byte is set.
+    pub fn bit(mut self, index: u8, bit: u8) -> DeviceMapBuilder {
+        if bit > 7 {
+            self.builder
+                .errors
+                .push(DeviceMapError::InvalidBit(self.button, bit));
+        }
+        self.query(
+            index,
+            ButtonQuery::Bit(1u8.checked_shl(bit as u32).unwrap_or(0)),
+        )
+    }
+
+    /// Pressed while any bit of the mask is set.
+    pub fn mask(self, index: u8, mask: u8) -> DeviceMapBuilder {
+        self.query(index, ButtonQuery::Bit(mask))
+    }
+
+    /// Pressed while a byte holds exactly this value.
+    pub fn eq(self, index: u8, value: u8) -> DeviceMapBuilder {
+        self.query(index, ButtonQuery::Eq(value))
+    }
+
+    /// Pressed while the masked byte holds this value.
+    pub fn masked(self, index: u8, mask: u8, value: u8) -> DeviceMapBuilder {
+        self.query(index, ButtonQuery::Masked(mask, value))
+    }
+
+    /// Pressed while a byte falls within an inclusive range.
+    pub fn range(self, index: u8, min: u8, max: u8) -> DeviceMapBuilder {
+        self.query(index, ButtonQuery::Range(min, max))
+    }
+
+    /// An analog button, such as a trigger, reading from released to fully pressed.
+    pub fn analog(self, index: u8, released: u8, pressed: u8) -> DeviceMapBuilder {
+        self.query(index, ButtonQuery::Analog(released, pressed))
+    }
+
+    /// Reads the button with any query.
+    pub fn query(self, index: u8, query: ButtonQuery) -> DeviceMapBuilder {
+        self.builder
+            .add_button(self.button, ButtonPointer(index, query))
+    }
+}
+
+impl AxisBuilder {
+    /// The number of coarse steps the axis spans.
+    pub fn octaves(mut self, octaves: u16) -> Self {
+        self.pointer.octaves = octaves;
+        self
+    }
+
+    pub fn inverted(mut self) -> Self {
+        self.pointer.inverted = true;
+        self
+    }
+
+    /// Reports values between 0 and 1 rather than -1 and 1, as for a throttle or pedal.
+    pub fn absolute(mut self) -> Self {
+        self.pointer.abs = true;
+        self
+    }
+
+    pub fn encoding(mut self, encoding: AxisEncoding) -> Self {
+        self.pointer.encoding = Some(encoding);
+        self
+    }
+
+    pub fn calibration(mut self, calibration: AxisCalibration) -> Self {
+        self.pointer.calibration = Some(calibration);
+        self
+    }
+
+    pub fn deadzone(mut self, deadzone: f32) -> Self {
+        self.pointer.deadzone = Some(deadzone);
+        self
+    }
+
+    pub fn saturation(mut self, saturation: f32) -> Self {
+        self.pointer.saturation = Some(saturation);
+        self
+    }
+
+    pub fn curve(mut self, curve: ResponseCurve) -> Self {
+        self.pointer.curve = curve;
+        self
+    }
+
+    pub fn zone(mut self, name: impl Into<String>, min: f32, max: f32) -> Self {
+        self.pointer.zones.push(AxisZone {
+            name: name.into(),
+            min,
+            max,
+        });
+        self
+    }
+
+    /// Reads the axis from a fine and coarse byte, and adds it.
+    pub fn bytes(self, fine: u8, coarse: u8) -> DeviceMapBuilder {
+        let AxisBuilder {
+            mut builder,
+            axis,
+            mut pointer,
+        } = self;
+        pointer.fine = fine;
+        pointer.coarse = coarse;
+        if builder.map.axes.insert(axis, pointer).is_some() {
+            builder.errors.push(DeviceMapError::Duplicate(axis.into()));
+        }
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::RelativeEncoding;
+
+    #[test]
+    fn test_builder_validation() {
+        let map = DeviceMap::builder()
+            .button(GamepadButton::South)
+            .bit(17, 2)
+            .axis(GamepadAxis::LeftZ)
+            .octaves(4)
+            .absolute()
+            .bytes(5, 6)
+            .build()
+            .unwrap();
+        assert_eq!(
+            map.buttons[&GamepadButton::South],
+            ButtonPointer(17, ButtonQuery::Bit(4))
+        );
+        assert_eq!(map.axes[&GamepadAxis::LeftZ].octaves, 4);
+
+        let duplicate = DeviceMap::builder()
+            .button(GamepadButton::South)
+            .bit(1, 0)
+            .button(GamepadButton::South)
+            .eq(2, 1)
+            .build();
+        assert_eq!(
+            duplicate,
+            Err(DeviceMapError::Duplicate(GamepadButton::South.into()))
+        );
+
+        let stick = |x, y| StickPair {
+            x,
+            y,
+            deadzone: None,
+            saturation: None,
+        };
+        let shared_stick = DeviceMap::builder()
+            .stick(stick(GamepadAxis::LeftStickX, GamepadAxis::LeftStickY))
+            .stick(stick(GamepadAxis::RightStickX, GamepadAxis::LeftStickY))
+            .build();
+        assert_eq!(
+            shared_stick,
+            Err(DeviceMapError::Duplicate(GamepadAxis::LeftStickY.into()))
+        );
+
+        let shared = DeviceMap::builder()
+            .axis(GamepadAxis::LeftStickX)
+            .bytes(3, 3)
+            .build();
+        let Err(DeviceMapError::Invalid(issue)) = shared else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(issue.issue, ValidationError::SharedAxisByte);
+        assert_eq!(issue.key, "LeftStickX");
+
+        let issue = |result| match result {
+            Err(DeviceMapError::Invalid(issue)) => (issue.section, issue.issue),
+            other => panic!("expected a validation error, got {other:?}"),
+        };
+        let axis_twice = DeviceMap::builder()
+            .axis(GamepadAxis::LeftZ)
+            .bytes(5, 6)
+            .button_axis(
+                GamepadAxis::LeftZ,
+                ButtonAxisPointer {
+                    negative: ButtonPointer(1, ButtonQuery::Bit(1)),
+                    positive: ButtonPointer(1, ButtonQuery::Bit(2)),
+                    ramp: None,
+                },
+            )
+            .build();
+        assert_eq!(
+            issue(axis_twice),
+            ("button_axes", ValidationError::AlsoBound("axes"))
+        );
+
+        let relative_twice = DeviceMap::builder()
+            .axis(GamepadAxis::LeftZ)
+            .bytes(5, 6)
+            .relative_axis(
+                GamepadAxis::LeftZ,
+                RelativePointer {
+                    index: 7,
+                    encoding: RelativeEncoding::Delta,
+                    bytes: None,
+                    increment: None,
+                    decrement: None,
+                },
+            )
+            .build();
+        assert_eq!(
+            issue(relative_twice),
+            ("relative_axes", ValidationError::AlsoBound("axes"))
+        );
+
+        let chord_twice = DeviceMap::builder()
+            .button(GamepadButton::South)
+            .bit(1, 0)
+            .chord(
+                GamepadButton::South,
+                Chord {
+                    buttons: vec![GamepadButton::East],
+                    window: None,
+                    suppress: false,
+                },
+            )
+            .build();
+        assert_eq!(
+            issue(chord_twice),
+            ("chords", ValidationError::AlsoBound("buttons"))
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::{assets::HidAsset, device::DeviceId, input::AxisCalibration};
+
+/// Links a connected hid's gamepad entity to the asset that maps it.
+#[derive(Debug, Clone, PartialEq, Component, Reflect)]
+pub struct HidDevice {
+    pub(crate) id: DeviceId,
+    pub(crate) asset: Handle<HidAsset>,
+}
+
+impl HidDevice {
+    /// The vendor and product id reported by the device.
+    pub fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    /// The asset mapping this device.
+    pub fn asset(&self) -> &Handle<HidAsset> {
+        &self.asset
+    }
+}
+
+/// Runtime calibration overrides for a connected hid. Insert this on the gamepad entity to replace the
+/// calibration declared in the device asset, e.g. after running a calibration routine for a worn stick.
+#[derive(Debug, Clone, Default, PartialEq, Component, Reflect)]
+pub struct HidCalibration(pub HashMap<GamepadAxis, AxisCalibration>);
+
+impl HidCalibration {
+    /// Gets the calibration override for an axis, if there is one.
+    pub fn get(&self, axis: GamepadAxis) -> Option<&AxisCalibration> {
+        self.0.get(&axis)
+    }
+
+    /// Sets the calibration override for an axis.
+    pub fn set(&mut self, axis: GamepadAxis, calibration: AxisCalibration) {
+        self.0.insert(axis, calibration);
+    }
+
+    /// Removes the override for an axis, falling back to the asset calibration.
+    pub fn clear(&mut self, axis: GamepadAxis) -> Option<AxisCalibration> {
+        self.0.remove(&axis)
+    }
+}
+
+/// The current position of each multi-position switch on a connected hid.
+#[derive(Debug, Clone, Default, PartialEq, Component, Reflect)]
+pub struct HidSwitches(pub(crate) HashMap<String, String>);
+
+impl HidSwitches {
+    /// Gets the current position of a switch. This is `None` if the switch is between positions or unknown.
+    pub fn get(&self, switch: &str) -> Option<&str> {
+        self.0.get(switch).map(String::as_str)
+    }
+
+    /// Sets the position of a switch, returning the previous one.
+    pub(crate) fn set(&mut self, switch: &str, position: Option<String>) -> Option<String> {
+        match position {
+            Some(position) => self.0.insert(switch.to_string(), position),
+            None => self.0.remove(switch),
+        }
+    }
+}
+
+/// The zones each axis on a connected hid currently sits in.
+#[derive(Debug, Clone, Default, PartialEq, Component, Reflect)]
+pub struct HidAxisZones(pub(crate) HashMap<GamepadAxis, HashSet<String>>);
+
+impl HidAxisZones {
+    /// Checks if an axis is inside the named zone.
+    pub fn contains(&self, axis: GamepadAxis, zone: &str) -> bool {
+        self.0.get(&axis).is_some_and(|zones| zones.contains(zone))
+    }
+
+    /// Iterates the names of the zones an axis is inside.
+    pub fn zones(&self, axis: GamepadAxis) -> impl Iterator<Item = &str> {
+        self.0.get(&axis).into_iter().flatten().map(String::as_str)
+    }
+
+    pub(crate) fn set(&mut self, axis: GamepadAxis, zone: &str, inside: bool) {
+        let zones = self.0.entry(axis).or_default();
+        if inside {
+            zones.insert(zone.to_string());
+        } else {
+            zones.remove(zone);
+        }
+    }
+}
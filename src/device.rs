@@ -1,27 +1,130 @@
-use crate::input::{AxisPointer, ButtonPointer};
+use crate::assets::{pretty_config, ron_options};
+use crate::bindings::{Binding, BindingLabel};
+use crate::input::{
+    AxisPointer, ButtonAxisPointer, ButtonPointer, RelativePointer, StickPair, SwitchPointer,
+};
+use crate::migration::SCHEMA_VERSION;
+use crate::serialization::{map_or_entries, sorted_map};
 use bevy::{
     asset::Asset,
     prelude::{GamepadAxis, GamepadButton},
     reflect::Reflect,
 };
 use hidapi::DeviceInfo;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// A readable asset from file.
-#[derive(Clone, Debug, Asset, Deserialize, Reflect)]
+#[derive(Clone, Debug, Asset, Serialize, Deserialize, Reflect)]
 pub struct DeviceAsset {
-    name: String,
-    pid: u16,
-    vid: u16,
-    mappings: DeviceMap,
+    /// The schema version the asset was written against. Assets without one predate versioning.
+    #[serde(default)]
+    pub(crate) version: u32,
+    pub(crate) name: String,
+    /// The vendor and product ids this mapping applies to. Templates that are only inherited from can leave this empty.
+    #[serde(default)]
+    pub(crate) ids: Vec<DeviceMatch>,
+    /// Another device asset to inherit bindings from, relative to this one. Bindings declared here override the base.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) base: Option<String>,
+    pub(crate) mappings: DeviceMap,
+    /// The locale labels are shown in when no [`HidLocale`](crate::resources::HidLocale) is set. This comes from
+    /// the loader settings rather than the file.
+    #[serde(skip)]
+    pub(crate) locale: Option<String>,
+}
+
+/// Identifies which devices a [`DeviceAsset`] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum DeviceMatch {
+    /// A single vendor and product id.
+    Id { vid: u16, pid: u16 },
+    /// Every product id from `min` to `max` (inclusive) for a vendor.
+    Range { vid: u16, min: u16, max: u16 },
 }
 
 /// This is where all data pertaining to a device is held.
-#[derive(Debug, Clone, PartialEq, Reflect, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Reflect, Serialize, Deserialize)]
 pub struct DeviceMap {
+    #[serde(serialize_with = "sorted_map", deserialize_with = "map_or_entries")]
     pub buttons: HashMap<GamepadButton, ButtonPointer>,
+    #[serde(serialize_with = "sorted_map", deserialize_with = "map_or_entries")]
     pub axes: HashMap<GamepadAxis, AxisPointer>,
+    /// Axes synthesized from a pair of buttons.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "sorted_map",
+        deserialize_with = "map_or_entries"
+    )]
+    pub button_axes: HashMap<GamepadAxis, ButtonAxisPointer>,
+    /// Axes that report movement rather than a position. These are sent as [`HidRelativeAxisMoved`] messages.
+    ///
+    /// [`HidRelativeAxisMoved`]: crate::events::HidRelativeAxisMoved
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "sorted_map",
+        deserialize_with = "map_or_entries"
+    )]
+    pub relative_axes: HashMap<GamepadAxis, RelativePointer>,
+    /// Multi-position switches, keyed by name. Their positions are kept in [`HidSwitches`].
+    ///
+    /// [`HidSwitches`]: crate::components::HidSwitches
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "sorted_map"
+    )]
+    pub switches: HashMap<String, SwitchPointer>,
+    /// Axes that are processed together as 2D sticks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sticks: Vec<StickPair>,
+    /// Alternate button layers, selected while their modifier is held. The first active layer wins.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<ShiftLayer>,
+    /// Virtual buttons that are pressed by holding a combination of buttons together.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "sorted_map",
+        deserialize_with = "map_or_entries"
+    )]
+    pub chords: HashMap<GamepadButton, Chord>,
+    /// Display names and glyphs for the device's inputs.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "sorted_map",
+        deserialize_with = "map_or_entries"
+    )]
+    pub labels: HashMap<Binding, BindingLabel>,
+}
+
+/// A virtual button that fires while a combination of physical buttons is held.
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct Chord {
+    /// The buttons (by their base binding) that make up the chord.
+    pub buttons: Vec<GamepadButton>,
+    /// The time in seconds between the first and last button press for the chord to count.
+    /// Without a window the buttons can be pressed in any order, at any pace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<f32>,
+    /// Releases the component buttons when the chord fires, and keeps them quiet until they are let go.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suppress: bool,
+}
+
+/// An alternate set of buttons that is active while a modifier (such as a HOTAS "shift" button) is held.
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct ShiftLayer {
+    pub name: String,
+    /// The source that activates the layer.
+    pub modifier: ButtonPointer,
+    /// Maps a button from the base layer onto the button it emits while the layer is active.
+    /// Buttons that are not listed keep their base binding.
+    #[serde(serialize_with = "sorted_map", deserialize_with = "map_or_entries")]
+    pub buttons: HashMap<GamepadButton, GamepadButton>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
@@ -42,30 +145,81 @@ impl DeviceId {
 impl DeviceAsset {
     pub fn new(name: String, pid: u16, vid: u16, mappings: DeviceMap) -> DeviceAsset {
         DeviceAsset {
+            version: SCHEMA_VERSION,
             name,
-            pid,
-            vid,
+            ids: vec![DeviceMatch::Id { vid, pid }],
+            base: None,
             mappings,
+            locale: None,
         }
     }
 
+    /// Adds another set of devices this mapping applies to, such as a Bluetooth or regional variant.
+    pub fn with_id(mut self, id: DeviceMatch) -> Self {
+        self.ids.push(id);
+        self
+    }
+
+    /// Sets the locale labels are shown in when no [`HidLocale`](crate::resources::HidLocale) is set.
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Sets the asset this one inherits its bindings from.
+    pub fn with_base(mut self, base: impl Into<String>) -> Self {
+        self.base = Some(base.into());
+        self
+    }
+
+    /// The path of the asset this one inherits its bindings from, if any.
+    pub fn base(&self) -> Option<&str> {
+        self.base.as_deref()
+    }
+
+    /// The schema version of the asset. Loaded assets are always migrated to [`SCHEMA_VERSION`].
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    // Product ID
+    /// The devices this mapping applies to.
+    pub fn ids(&self) -> &[DeviceMatch] {
+        &self.ids
+    }
+
+    /// Product ID of the first device this mapping applies to, or 0 if there are none.
+    #[deprecated(note = "assets can cover several devices, use `ids` instead")]
     pub fn pid(&self) -> u16 {
-        self.pid
+        DeviceId::from(self).product_id
     }
 
-    /// Vendor ID
+    /// Vendor ID of the first device this mapping applies to, or 0 if there are none.
+    #[deprecated(note = "assets can cover several devices, use `ids` instead")]
     pub fn vid(&self) -> u16 {
-        self.vid
+        DeviceId::from(self).vendor_id
+    }
+
+    /// Checks if the mapping applies to a device.
+    pub fn matches(&self, id: &DeviceId) -> bool {
+        self.ids.iter().any(|m| m.matches(id))
     }
 
     pub fn mappings(&self) -> &DeviceMap {
         &self.mappings
     }
+
+    pub fn mappings_mut(&mut self) -> &mut DeviceMap {
+        &mut self.mappings
+    }
+
+    /// Writes the asset as pretty-printed RON, in the same `.hid.ron` format the asset loader reads.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron_options().to_string_pretty(self, pretty_config())
+    }
 }
 
 impl DeviceMap {
@@ -73,32 +227,189 @@ impl DeviceMap {
         buttons: HashMap<GamepadButton, ButtonPointer>,
         axes: HashMap<GamepadAxis, AxisPointer>,
     ) -> Self {
-        Self { buttons, axes }
+        Self {
+            buttons,
+            axes,
+            button_axes: HashMap::new(),
+            relative_axes: HashMap::new(),
+            switches: HashMap::new(),
+            sticks: Vec::new(),
+            layers: Vec::new(),
+            chords: HashMap::new(),
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Adds axes that report movement rather than a position.
+    pub fn with_relative_axes(
+        mut self,
+        relative_axes: HashMap<GamepadAxis, RelativePointer>,
+    ) -> Self {
+        self.relative_axes = relative_axes;
+        self
+    }
+
+    /// Adds multi-position switches.
+    pub fn with_switches(mut self, switches: HashMap<String, SwitchPointer>) -> Self {
+        self.switches = switches;
+        self
+    }
+
+    /// Pairs up axes to be processed as 2D sticks.
+    pub fn with_sticks(mut self, sticks: Vec<StickPair>) -> Self {
+        self.sticks = sticks;
+        self
+    }
+
+    /// Adds shift layers.
+    pub fn with_layers(mut self, layers: Vec<ShiftLayer>) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Adds chorded virtual buttons.
+    pub fn with_chords(mut self, chords: HashMap<GamepadButton, Chord>) -> Self {
+        self.chords = chords;
+        self
+    }
+
+    /// Fills in anything this map doesn't declare from a base map. Bindings already present take priority,
+    /// as do layers with the same name and sticks sharing an axis.
+    pub fn inherit(&mut self, base: &DeviceMap) {
+        fn merge<K: Clone + Eq + std::hash::Hash, V: Clone>(
+            map: &mut HashMap<K, V>,
+            base: &HashMap<K, V>,
+        ) {
+            for (key, value) in base {
+                map.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+        merge(&mut self.buttons, &base.buttons);
+        merge(&mut self.axes, &base.axes);
+        merge(&mut self.button_axes, &base.button_axes);
+        merge(&mut self.relative_axes, &base.relative_axes);
+        merge(&mut self.switches, &base.switches);
+        merge(&mut self.chords, &base.chords);
+        merge(&mut self.labels, &base.labels);
+        for stick in &base.sticks {
+            if self.stick(stick.x).is_none() && self.stick(stick.y).is_none() {
+                self.sticks.push(*stick);
+            }
+        }
+        for layer in &base.layers {
+            if !self.layers.iter().any(|l| l.name == layer.name) {
+                self.layers.push(layer.clone());
+            }
+        }
+    }
+
+    /// Adds display labels for the device's inputs.
+    pub fn with_labels(mut self, labels: HashMap<Binding, BindingLabel>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Gets the display label for an input.
+    pub fn label_for(&self, binding: impl Into<Binding>) -> Option<&BindingLabel> {
+        self.labels.get(&binding.into())
+    }
+
+    /// Finds the layer selected by the given buffer, if any.
+    pub fn active_layer(&self, buf: &[u8]) -> Option<&ShiftLayer> {
+        self.layers.iter().find(|layer| layer.modifier.pressed(buf))
+    }
+
+    /// The button a base button emits under the given layer.
+    pub fn resolve(&self, button: GamepadButton, layer: Option<&ShiftLayer>) -> GamepadButton {
+        layer
+            .and_then(|layer| layer.buttons.get(&button))
+            .copied()
+            .unwrap_or(button)
+    }
+
+    /// Finds the stick an axis belongs to, if any. Sticks missing either axis are skipped, leaving
+    /// the bound one to be read as a plain axis.
+    pub fn stick(&self, axis: GamepadAxis) -> Option<&StickPair> {
+        self.sticks.iter().find(|stick| {
+            stick.contains(axis)
+                && self.axes.contains_key(&stick.x)
+                && self.axes.contains_key(&stick.y)
+        })
+    }
+
+    /// Adds axes that are driven by pairs of buttons.
+    pub fn with_button_axes(
+        mut self,
+        button_axes: HashMap<GamepadAxis, ButtonAxisPointer>,
+    ) -> Self {
+        self.button_axes = button_axes;
+        self
     }
 
     pub fn len(&self) -> usize {
-        self.buttons.len() + self.axes.len()
+        self.buttons.len()
+            + self.axes.len()
+            + self.button_axes.len()
+            + self.relative_axes.len()
+            + self.switches.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.buttons.is_empty() && self.axes.is_empty()
+        self.buttons.is_empty()
+            && self.axes.is_empty()
+            && self.button_axes.is_empty()
+            && self.relative_axes.is_empty()
+            && self.switches.is_empty()
     }
 }
 
-impl From<&DeviceInfo> for DeviceId {
-    fn from(device: &DeviceInfo) -> Self {
-        Self {
-            vendor_id: device.vendor_id(),
-            product_id: device.product_id(),
+impl DeviceMatch {
+    /// Checks if a device falls under this match.
+    pub fn matches(&self, id: &DeviceId) -> bool {
+        match *self {
+            DeviceMatch::Id { vid, pid } => id.vendor_id == vid && id.product_id == pid,
+            DeviceMatch::Range { vid, min, max } => {
+                id.vendor_id == vid && (min..=max).contains(&id.product_id)
+            }
         }
     }
 }
 
+impl Chord {
+    /// Checks the component buttons were pressed close enough together, given the time each was pressed at.
+    pub fn within_window(&self, pressed_at: &HashMap<GamepadButton, f32>) -> bool {
+        let Some(window) = self.window else {
+            return true;
+        };
+        let times = self
+            .buttons
+            .iter()
+            .map(|button| pressed_at.get(button).copied());
+        let Some(times) = times.collect::<Option<Vec<_>>>() else {
+            return false;
+        };
+        let first = times.iter().copied().fold(f32::INFINITY, f32::min);
+        let last = times.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        last - first <= window
+    }
+}
+
+/// The first device an asset applies to. A range counts as its lowest product id.
 impl From<&DeviceAsset> for DeviceId {
     fn from(device: &DeviceAsset) -> Self {
+        match device.ids.first() {
+            Some(DeviceMatch::Id { vid, pid }) => Self::new(*vid, *pid),
+            Some(DeviceMatch::Range { vid, min, .. }) => Self::new(*vid, *min),
+            None => Self::new(0, 0),
+        }
+    }
+}
+
+impl From<&DeviceInfo> for DeviceId {
+    fn from(device: &DeviceInfo) -> Self {
         Self {
-            vendor_id: device.vid(),
-            product_id: device.pid(),
+            vendor_id: device.vendor_id(),
+            product_id: device.product_id(),
         }
     }
 }
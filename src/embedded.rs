@@ -0,0 +1,112 @@
+use bevy::{asset::io::embedded::EmbeddedAssetRegistry, prelude::*};
+use std::path::Path;
+
+/// The asset folder the bundled mappings are registered under.
+pub const EMBEDDED_FOLDER: &str = "embedded://bevy_hid/hid";
+
+/// The mappings bundled with the crate, keyed by file name without the extension. Only mappings that have been checked
+/// against real hardware are bundled; more HOTAS, wheels, pedals and button boxes will be added separately.
+const DEVICES: &[(&str, &[u8])] = &[
+    (
+        "nxt_gladiator",
+        include_bytes!("../assets/hid/nxt_gladiator.hid.ron"),
+    ),
+    (
+        "Thrustmaster_throttle",
+        include_bytes!("../assets/hid/Thrustmaster_throttle.hid.ron"),
+    ),
+];
+
+/// Selects which of the bundled mappings are registered.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EmbeddedDevices {
+    #[default]
+    All,
+    None,
+    /// Only the named devices.
+    Only(Vec<String>),
+    /// Every device except the named ones.
+    Except(Vec<String>),
+}
+
+impl EmbeddedDevices {
+    /// Whether the named bundled device is selected.
+    pub fn includes(&self, device: &str) -> bool {
+        match self {
+            EmbeddedDevices::All => true,
+            EmbeddedDevices::None => false,
+            EmbeddedDevices::Only(devices) => devices.iter().any(|name| name == device),
+            EmbeddedDevices::Except(devices) => !devices.iter().any(|name| name == device),
+        }
+    }
+
+    fn named(&self) -> &[String] {
+        match self {
+            EmbeddedDevices::Only(devices) | EmbeddedDevices::Except(devices) => devices,
+            _ => &[],
+        }
+    }
+}
+
+/// The names of every bundled mapping, for use with [`EmbeddedDevices`].
+pub fn embedded_devices() -> impl Iterator<Item = &'static str> {
+    DEVICES.iter().map(|(name, _)| *name)
+}
+
+/// Adds the selected bundled mappings to the embedded asset source. Returns false if none were added.
+pub(crate) fn register_embedded_devices(app: &mut App, selection: &EmbeddedDevices) -> bool {
+    for name in selection.named() {
+        if !embedded_devices().any(|device| device == name) {
+            warn!("There is no embedded hid mapping named {}", name);
+        }
+    }
+
+    let Some(registry) = app.world().get_resource::<EmbeddedAssetRegistry>() else {
+        error!("Embedded hid mappings need the AssetPlugin to be added first");
+        return false;
+    };
+
+    let mut registered = false;
+    for (name, bytes) in DEVICES.iter().filter(|(name, _)| selection.includes(name)) {
+        let file = format!("{name}.hid.ron");
+        let full_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets/hid")
+            .join(&file);
+        registry.insert_asset(full_path, &Path::new("bevy_hid/hid").join(&file), *bytes);
+        registered = true;
+    }
+    registered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{migration::migrate, validation::REPORT_SIZE};
+
+    #[test]
+    fn test_embedded_devices_load() {
+        for (name, bytes) in DEVICES {
+            let (device, warnings) = migrate(bytes).unwrap();
+            assert!(warnings.is_empty(), "{name} is out of date");
+            let report = device.validate_report_size(REPORT_SIZE);
+            assert!(
+                report.errors.is_empty(),
+                "{name} is invalid: {:?}",
+                report.errors
+            );
+        }
+        // New mappings in the library have to be bundled as well.
+        let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/hid");
+        for entry in std::fs::read_dir(folder).unwrap() {
+            let file = entry.unwrap().file_name().into_string().unwrap();
+            let Some(name) = file.strip_suffix(".hid.ron") else {
+                continue;
+            };
+            assert!(
+                embedded_devices().any(|device| device == name),
+                "{file} is not bundled"
+            );
+        }
+        assert!(!EmbeddedDevices::Except(vec!["nxt_gladiator".into()]).includes("nxt_gladiator"));
+    }
+}
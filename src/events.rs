@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+/// Sent when a relative axis (such as a rotary encoder) moves. Bevy gamepad axes are absolute, so movement
+/// is reported through this message instead.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct HidRelativeAxisMoved {
+    /// The gamepad the axis belongs to.
+    pub gamepad: Entity,
+    /// The axis that moved.
+    pub axis: GamepadAxis,
+    /// The number of steps moved since the last report.
+    pub delta: i32,
+}
+
+impl HidRelativeAxisMoved {
+    pub fn new(gamepad: Entity, axis: GamepadAxis, delta: i32) -> Self {
+        Self {
+            gamepad,
+            axis,
+            delta,
+        }
+    }
+}
+
+/// Sent when a multi-position switch moves to a new position.
+#[derive(Message, Debug, Clone, PartialEq, Reflect)]
+pub struct HidSwitchChanged {
+    /// The gamepad the switch belongs to.
+    pub gamepad: Entity,
+    /// The name of the switch, as declared in the device asset.
+    pub switch: String,
+    /// The position the switch is now in, if any position matches.
+    pub position: Option<String>,
+    /// The position the switch was in before.
+    pub previous: Option<String>,
+}
+
+/// Sent when an axis enters or leaves one of its zones.
+#[derive(Message, Debug, Clone, PartialEq, Reflect)]
+pub struct HidAxisZoneChanged {
+    /// The gamepad the axis belongs to.
+    pub gamepad: Entity,
+    /// The axis that moved.
+    pub axis: GamepadAxis,
+    /// The name of the zone, as declared in the device asset.
+    pub zone: String,
+    /// Whether the axis entered or left the zone.
+    pub transition: ZoneTransition,
+}
+
+/// The direction an axis crossed a zone boundary in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum ZoneTransition {
+    Entered,
+    Left,
+}
@@ -0,0 +1,135 @@
+use bevy::reflect::TypePath;
+use serde::de::DeserializeOwned;
+
+use crate::assets::{HidAssetLoadError, ron_options};
+
+/// A text format that device assets can be written in.
+pub(crate) trait AssetFormat: TypePath + Send + Sync {
+    /// The file extensions handled by this format.
+    const EXTENSIONS: &'static [&'static str];
+
+    fn parse<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, HidAssetLoadError>;
+
+    /// The `version` field as it would be written in this format, for migration hints.
+    fn version_field(version: u32) -> String;
+
+    /// An `ids` list holding a single id as it would be written in this format, for migration hints.
+    fn ids_field(vid: u16, pid: u16) -> String;
+}
+
+/// `.hid.ron`, the native asset format.
+#[derive(TypePath)]
+pub(crate) struct Ron;
+
+impl AssetFormat for Ron {
+    const EXTENSIONS: &'static [&'static str] = &["hid.ron"];
+
+    fn parse<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, HidAssetLoadError> {
+        Ok(ron_options().from_bytes(bytes)?)
+    }
+
+    fn version_field(version: u32) -> String {
+        format!("version: {version}")
+    }
+
+    fn ids_field(vid: u16, pid: u16) -> String {
+        format!("ids: [Id(vid: {vid}, pid: {pid})]")
+    }
+}
+
+/// `.hid.json`. Maps keyed by bindings with data, such as `Other(1)`, are written as lists of `[key, value]` entries.
+#[cfg(feature = "json")]
+#[derive(TypePath)]
+pub(crate) struct Json;
+
+#[cfg(feature = "json")]
+impl AssetFormat for Json {
+    const EXTENSIONS: &'static [&'static str] = &["hid.json"];
+
+    fn parse<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, HidAssetLoadError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    fn version_field(version: u32) -> String {
+        format!(r#""version": {version}"#)
+    }
+
+    fn ids_field(vid: u16, pid: u16) -> String {
+        format!(r#""ids": [{{"Id": {{"vid": {vid}, "pid": {pid}}}}}]"#)
+    }
+}
+
+/// `.hid.toml`. Like JSON, maps keyed by bindings with data are written as lists of `[key, value]` entries.
+#[cfg(feature = "toml")]
+#[derive(TypePath)]
+pub(crate) struct Toml;
+
+#[cfg(feature = "toml")]
+impl AssetFormat for Toml {
+    const EXTENSIONS: &'static [&'static str] = &["hid.toml"];
+
+    fn parse<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, HidAssetLoadError> {
+        Ok(toml::from_slice(bytes)?)
+    }
+
+    fn version_field(version: u32) -> String {
+        format!("version = {version}")
+    }
+
+    fn ids_field(vid: u16, pid: u16) -> String {
+        format!("ids = [{{ Id = {{ vid = {vid}, pid = {pid} }} }}]")
+    }
+}
+
+#[cfg(all(test, any(feature = "json", feature = "toml")))]
+mod tests {
+    use super::*;
+    use crate::migration::{migrate, migrate_from};
+
+    const RON: &str = r#"(version: 2, name: "Pad", ids: [Id(vid: 2, pid: 1)], mappings: (
+        buttons: { South: (3, Bit(1)), Other(1): (3, Bit(2)) },
+        axes: { LeftStickX: (fine: 1, coarse: 2, octaves: 256, inverted: false, abs: false) },
+    ))"#;
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_matches_ron() {
+        let json = r#"{"version": 2, "name": "Pad", "ids": [{"Id": {"vid": 2, "pid": 1}}], "mappings": {
+            "buttons": [["South", [3, {"Bit": 1}]], [{"Other": 1}, [3, {"Bit": 2}]]],
+            "axes": {"LeftStickX": {"fine": 1, "coarse": 2, "octaves": 256, "inverted": false, "abs": false}}
+        }}"#;
+        let (from_json, warnings) = migrate_from::<Json>(json.as_bytes()).unwrap();
+        let (from_ron, _) = migrate(RON.as_bytes()).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(from_json.mappings(), from_ron.mappings());
+
+        let legacy =
+            r#"{"name": "Pad", "pid": 1, "vid": 2, "mappings": {"buttons": {}, "axes": {}}}"#;
+        let (_, warnings) = migrate_from::<Json>(legacy.as_bytes()).unwrap();
+        assert!(warnings[0].ends_with(r#"add `"version": 2`"#));
+        assert!(warnings[1].contains(r#"`"ids": [{"Id": {"vid": 2, "pid": 1}}]`"#));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_matches_ron() {
+        let toml = r#"
+            version = 2
+            name = "Pad"
+            ids = [{ Id = { vid = 2, pid = 1 } }]
+
+            [mappings]
+            buttons = [["South", [3, { Bit = 1 }]], [{ Other = 1 }, [3, { Bit = 2 }]]]
+            axes = { LeftStickX = { fine = 1, coarse = 2, octaves = 256, inverted = false, abs = false } }
+        "#;
+        let (from_toml, warnings) = migrate_from::<Toml>(toml.as_bytes()).unwrap();
+        let (from_ron, _) = migrate(RON.as_bytes()).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(from_toml.mappings(), from_ron.mappings());
+
+        let legacy = "name = \"Pad\"\npid = 1\nvid = 2\n[mappings]\nbuttons = {}\naxes = {}";
+        let (_, warnings) = migrate_from::<Toml>(legacy.as_bytes()).unwrap();
+        assert!(warnings[0].ends_with("add `version = 2`"));
+        assert!(warnings[1].contains("`ids = [{ Id = { vid = 2, pid = 1 } }]`"));
+    }
+}
@@ -1,8 +1,11 @@
-use bevy::reflect::Reflect;
-use serde::Deserialize;
+use bevy::{
+    input::gamepad::{GamepadAxis, GamepadButton},
+    reflect::Reflect,
+};
+use serde::{Deserialize, Serialize};
 
 /// Splits input into four components.
-#[derive(Debug, Clone, PartialEq, Deserialize, Reflect)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
 pub enum InputType {
     /// A single button.
     Button(ButtonPointer),
@@ -10,10 +13,10 @@ pub enum InputType {
     Axis(AxisPointer),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Reflect)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
 pub struct ButtonPointer(pub u8, pub ButtonQuery);
 
-#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Default, Reflect)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default, Reflect)]
 pub struct AxisPointer {
     /// The buffer index on which the fine value is stored. This combines the the coarse value to get the total axis value.
     pub fine: u8,
@@ -25,13 +28,619 @@ pub struct AxisPointer {
     pub inverted: bool,
     /// An absolute axis will only have values between 0 and 1 (once normalized).
     pub abs: bool,
+    /// How the fine and coarse bytes combine. Axes without one use the loader's default, which is [`AxisEncoding::Octaves`]
+    /// unless configured otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<AxisEncoding>,
+    /// The physical range of the axis. Without calibration the full raw range is assumed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<AxisCalibration>,
+    /// Normalized values with a magnitude below this are reported as zero.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadzone: Option<f32>,
+    /// Normalized values with a magnitude above this are reported as full deflection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saturation: Option<f32>,
+    /// The response curve applied once the deadzone and saturation have been taken out.
+    #[serde(default, skip_serializing_if = "ResponseCurve::is_linear")]
+    pub curve: ResponseCurve,
+    /// Named zones along the axis, such as detents on a throttle.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub zones: Vec<AxisZone>,
+}
+
+/// How the fine and coarse bytes of an axis combine into a raw value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum AxisEncoding {
+    /// `coarse * 255 + fine`, spanning `255 * octaves`.
+    #[default]
+    Octaves,
+    /// A little-endian unsigned value, `coarse << 8 | fine`, spanning `256 * octaves - 1`.
+    Unsigned,
+    /// A little-endian signed (two's complement) 16 bit value, centered on zero.
+    Signed,
+}
+
+/// A named section of an axis, in final (normalized and shaped) units.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct AxisZone {
+    pub name: String,
+    /// The lowest value inside the zone.
+    pub min: f32,
+    /// The highest value inside the zone.
+    pub max: f32,
+}
+
+/// A relative axis, such as a rotary encoder or trackball, that reports movement rather than a position.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct RelativePointer {
+    /// The buffer index of the first (least significant) byte.
+    pub index: u8,
+    /// How the movement is encoded in the buffer.
+    pub encoding: RelativeEncoding,
+    /// The number of little-endian bytes the value spans. Defaults to a single byte.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u8>,
+    /// A button that is pulsed once for every step the axis moves in the positive direction. Steps beyond the first
+    /// are sent on the following frames.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub increment: Option<GamepadButton>,
+    /// A button that is pulsed once for every step the axis moves in the negative direction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decrement: Option<GamepadButton>,
+}
+
+/// How a relative axis stores its movement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum RelativeEncoding {
+    /// A counter that wraps around. Movement is the difference between reports.
+    Counter,
+    /// A signed (two's complement) delta, reported fresh each report.
+    Delta,
+}
+
+/// A multi-position switch, such as a 3-way toggle or rotary selector.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct SwitchPointer {
+    /// Each named position and the button query that identifies it. The first matching position wins.
+    pub positions: Vec<(String, ButtonPointer)>,
+    /// The position reported when none of the others match, e.g. the center of a spring-loaded switch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// Two axes that form a 2D stick. The pair is given a radial deadzone rather than a square one.
+/// Deadzone and saturation on the individual axes are ignored once they are paired, though their curves still apply.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct StickPair {
+    /// The horizontal axis.
+    pub x: GamepadAxis,
+    /// The vertical axis.
+    pub y: GamepadAxis,
+    /// Sticks deflected by less than this distance from the center are reported as centered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadzone: Option<f32>,
+    /// Sticks deflected beyond this distance are reported at full deflection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saturation: Option<f32>,
+}
+
+/// Shapes how an axis responds across its range.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default, Reflect)]
+pub enum ResponseCurve {
+    /// Output follows input directly.
+    #[default]
+    Linear,
+    /// Raises the magnitude to the given power. Values above 1 soften the center of the axis.
+    Exponential(f32),
+    /// Linear interpolation through (input, output) points, both in the range 0..1 and sorted by input.
+    /// The curve is anchored at (0, 0) and (1, 1).
+    Spline(Vec<(f32, f32)>),
+}
+
+/// The physical range of an axis, in raw (pre-normalized) units.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct AxisCalibration {
+    /// The lowest raw value the axis reaches.
+    pub min: u32,
+    /// The raw value the axis rests at. Defaults to halfway between min and max.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub center: Option<u32>,
+    /// The highest raw value the axis reaches.
+    pub max: u32,
+}
+
+/// A synthetic axis driven by a pair of buttons, such as a rocker or trim wheel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct ButtonAxisPointer {
+    /// The button that drives the axis towards -1.
+    pub negative: ButtonPointer,
+    /// The button that drives the axis towards 1.
+    pub positive: ButtonPointer,
+    /// Units per second the axis accelerates by while held. Without a ramp the axis snaps straight to full deflection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ramp: Option<f32>,
 }
 
 /// Rules for collecting button data from input buffers.
-#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Reflect)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
 pub enum ButtonQuery {
     /// A bit query does an & opperation on the input buffer.
     Bit(u8),
     /// Eq requires the buffer to be an exact match to the provided value.
     Eq(u8),
+    /// Masks the buffer before comparing it to a value, e.g. `Masked(0xF0, 0x30)` for a hat stored in the high nibble.
+    Masked(u8, u8),
+    /// The buffer falls within an inclusive range of values.
+    Range(u8, u8),
+    /// The buffer matches any of the listed values.
+    In(Vec<u8>),
+    /// Inverts other queries, matching only when none of them do.
+    Not(Vec<ButtonQuery>),
+    /// A bit query over two bytes, read little-endian from the pointer's index.
+    Bit16(u16),
+    /// A bit query over four bytes, read little-endian from the pointer's index.
+    Bit32(u32),
+    /// A pressure sensitive button, reading from its released value to its fully pressed value.
+    /// The button counts as held once the buffer moves past the released value.
+    Analog(u8, u8),
+    /// A digital bit for the held state, with the pressure reported in a separate byte.
+    BitPressure(u8, PressurePointer),
+}
+
+/// A byte that reports how hard a button is pressed, from its released value to its fully pressed value.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct PressurePointer {
+    pub index: u8,
+    pub released: u8,
+    pub pressed: u8,
+}
+
+impl PressurePointer {
+    /// Reads the normalized pressure from the buffer.
+    pub fn value(&self, buf: &[u8]) -> f32 {
+        let Some(&value) = buf.get(self.index as usize) else {
+            return 0.0;
+        };
+        if self.released == self.pressed {
+            return if value == self.pressed { 1.0 } else { 0.0 };
+        }
+        let (released, pressed, value) = (self.released as f32, self.pressed as f32, value as f32);
+        ((value - released) / (pressed - released)).clamp(0.0, 1.0)
+    }
+}
+
+impl ButtonPointer {
+    /// Checks whether the button is held in the given buffer.
+    pub fn pressed(&self, buf: &[u8]) -> bool {
+        self.1.matches(buf, self.0)
+    }
+
+    /// The value of the button in the given buffer. Digital buttons are either 0 or 1, while analog buttons
+    /// report their normalized pressure.
+    pub fn value(&self, buf: &[u8]) -> f32 {
+        match self.1 {
+            ButtonQuery::Analog(released, pressed) => PressurePointer {
+                index: self.0,
+                released,
+                pressed,
+            }
+            .value(buf),
+            ButtonQuery::BitPressure(_, pressure) if self.pressed(buf) => pressure.value(buf),
+            _ => {
+                if self.pressed(buf) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    /// The buffer indices this button reads from.
+    pub fn indices(&self) -> impl Iterator<Item = u8> + use<> {
+        let start = self.0;
+        let pressure = match self.1 {
+            ButtonQuery::BitPressure(_, pressure) => Some(pressure.index),
+            _ => None,
+        };
+        (0..self.1.width())
+            .filter_map(move |offset| start.checked_add(offset))
+            .chain(pressure)
+    }
+}
+
+impl AxisPointer {
+    /// The largest raw value the axis can report.
+    pub fn max_raw(&self) -> u32 {
+        match self.encoding.unwrap_or_default() {
+            AxisEncoding::Octaves => 255 * self.octaves as u32,
+            AxisEncoding::Unsigned => (256 * self.octaves as u32).saturating_sub(1),
+            AxisEncoding::Signed => u16::MAX as u32,
+        }
+    }
+
+    /// Reads the raw value of the axis from the buffer. Signed axes are offset so the raw value is never negative.
+    pub fn raw(&self, buf: &[u8]) -> u32 {
+        let (fine, coarse) = (buf[self.fine as usize], buf[self.coarse as usize]);
+        match self.encoding.unwrap_or_default() {
+            AxisEncoding::Octaves => (coarse as u32 * 255) + fine as u32,
+            AxisEncoding::Unsigned => u16::from_le_bytes([fine, coarse]) as u32,
+            AxisEncoding::Signed => (i16::from_le_bytes([fine, coarse]) as i32 + 0x8000) as u32,
+        }
+    }
+
+    /// Checks if either byte of the axis differs between two buffers.
+    pub fn changed(&self, buf_new: &[u8], buf_last: &[u8]) -> bool {
+        buf_new[self.fine as usize] != buf_last[self.fine as usize]
+            || buf_new[self.coarse as usize] != buf_last[self.coarse as usize]
+    }
+
+    /// Reads the final axis value from the buffer, applying calibration, deadzones and the response curve.
+    pub fn value(&self, buf: &[u8], calibration: Option<&AxisCalibration>) -> f32 {
+        self.shape(self.normalize(buf, calibration))
+    }
+
+    /// Applies the deadzone, saturation and response curve to a normalized value.
+    pub fn shape(&self, value: f32) -> f32 {
+        let deadzone = self.deadzone.unwrap_or(0.0);
+        let saturation = self.saturation.unwrap_or(1.0);
+        let magnitude = value.abs();
+        if magnitude <= deadzone {
+            return 0.0;
+        }
+        let magnitude = if saturation > deadzone {
+            ((magnitude - deadzone) / (saturation - deadzone)).min(1.0)
+        } else {
+            1.0
+        };
+        self.curve.apply(magnitude).copysign(value)
+    }
+
+    /// Normalizes the axis value found in the buffer, using the given calibration in place of the mapped one.
+    pub fn normalize(&self, buf: &[u8], calibration: Option<&AxisCalibration>) -> f32 {
+        let calibration = calibration
+            .or(self.calibration.as_ref())
+            .copied()
+            .unwrap_or(AxisCalibration {
+                min: 0,
+                center: (self.encoding == Some(AxisEncoding::Signed)).then_some(0x8000),
+                max: self.max_raw(),
+            });
+        let normalized = calibration.normalize(self.raw(buf), self.abs);
+        match (self.inverted, self.abs) {
+            (false, _) => normalized,
+            (true, false) => -normalized,
+            (true, true) => 1.0 - normalized,
+        }
+    }
+}
+
+impl AxisCalibration {
+    /// Maps a raw value onto 0..1 for absolute axes, or -1..1 around the center otherwise.
+    pub fn normalize(&self, raw: u32, abs: bool) -> f32 {
+        if self.max <= self.min {
+            return 0.0;
+        }
+        let raw = raw.clamp(self.min, self.max) as f32;
+        let (min, max) = (self.min as f32, self.max as f32);
+        if abs {
+            return (raw - min) / (max - min);
+        }
+        let center = self
+            .center
+            .map(|c| c.clamp(self.min, self.max) as f32)
+            .unwrap_or((min + max) * 0.5);
+        let normalized = if raw >= center {
+            if max > center {
+                (raw - center) / (max - center)
+            } else {
+                0.0
+            }
+        } else if center > min {
+            (raw - center) / (center - min)
+        } else {
+            0.0
+        };
+        normalized.clamp(-1.0, 1.0)
+    }
+}
+
+impl RelativePointer {
+    /// The number of bytes the value spans.
+    pub fn width(&self) -> u8 {
+        self.bytes.unwrap_or(1).clamp(1, 4)
+    }
+
+    /// Reads the unsigned raw value from the buffer.
+    pub fn raw(&self, buf: &[u8]) -> u32 {
+        (0..self.width()).fold(0, |acc, offset| {
+            let byte = buf
+                .get(self.index as usize + offset as usize)
+                .copied()
+                .unwrap_or_default();
+            acc | (byte as u32) << (8 * offset)
+        })
+    }
+
+    /// The movement between two reports. Counters that wrap around are handled as the shortest step.
+    pub fn delta(&self, buf_new: &[u8], buf_last: &[u8]) -> i32 {
+        let bits = 8 * self.width() as u32;
+        let raw = match self.encoding {
+            RelativeEncoding::Counter => self.raw(buf_new).wrapping_sub(self.raw(buf_last)),
+            RelativeEncoding::Delta => self.raw(buf_new),
+        };
+        // Sign extend from the width of the field.
+        let shift = 32 - bits;
+        ((raw << shift) as i32) >> shift
+    }
+}
+
+impl SwitchPointer {
+    /// Finds the position the switch is in.
+    pub fn position(&self, buf: &[u8]) -> Option<&str> {
+        self.positions
+            .iter()
+            .find(|(_, ptr)| ptr.pressed(buf))
+            .map(|(name, _)| name.as_str())
+            .or(self.default.as_deref())
+    }
+}
+
+impl StickPair {
+    /// Checks if the stick uses the given axis.
+    pub fn contains(&self, axis: GamepadAxis) -> bool {
+        self.x == axis || self.y == axis
+    }
+
+    /// Applies the radial deadzone and saturation to a pair of normalized values.
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        let deadzone = self.deadzone.unwrap_or(0.0);
+        let saturation = self.saturation.unwrap_or(1.0);
+        let magnitude = x.hypot(y);
+        if magnitude <= deadzone {
+            return (0.0, 0.0);
+        }
+        let scaled = if saturation > deadzone {
+            ((magnitude - deadzone) / (saturation - deadzone)).min(1.0)
+        } else {
+            1.0
+        };
+        let scale = scaled / magnitude;
+        ((x * scale).clamp(-1.0, 1.0), (y * scale).clamp(-1.0, 1.0))
+    }
+}
+
+impl AxisZone {
+    /// Checks if an axis value sits inside the zone.
+    pub fn contains(&self, value: f32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+}
+
+impl ResponseCurve {
+    pub fn is_linear(&self) -> bool {
+        matches!(self, ResponseCurve::Linear)
+    }
+
+    /// Maps a magnitude in the range 0..1 through the curve.
+    pub fn apply(&self, magnitude: f32) -> f32 {
+        let magnitude = magnitude.clamp(0.0, 1.0);
+        match self {
+            ResponseCurve::Linear => magnitude,
+            ResponseCurve::Exponential(exponent) => magnitude.powf(*exponent),
+            ResponseCurve::Spline(points) => {
+                let mut last = (0.0, 0.0);
+                for &(x, y) in points.iter().chain(std::iter::once(&(1.0, 1.0))) {
+                    if magnitude <= x {
+                        if x <= last.0 {
+                            return y;
+                        }
+                        let t = (magnitude - last.0) / (x - last.0);
+                        return last.1 + (y - last.1) * t;
+                    }
+                    last = (x, y);
+                }
+                last.1
+            }
+        }
+        .clamp(0.0, 1.0)
+    }
+}
+
+impl ButtonAxisPointer {
+    /// The value the axis is heading towards for the given buffer, ignoring any ramp.
+    pub fn target(&self, buf: &[u8]) -> f32 {
+        let negative = if self.negative.pressed(buf) { 1.0 } else { 0.0 };
+        let positive = if self.positive.pressed(buf) { 1.0 } else { 0.0 };
+        positive - negative
+    }
+
+    /// Advances the axis from its current value by `delta` seconds.
+    pub fn step(&self, current: f32, buf: &[u8], delta: f32) -> f32 {
+        let target = self.target(buf);
+        let Some(ramp) = self.ramp else {
+            return target;
+        };
+        if target == 0.0 {
+            return 0.0;
+        }
+        // Reversing direction starts the ramp again from the center.
+        let current = if current * target < 0.0 { 0.0 } else { current };
+        (current + target * ramp * delta).clamp(-1.0, 1.0)
+    }
+}
+
+impl ButtonQuery {
+    /// Checks the query against the buffer, starting at the given index.
+    pub fn matches(&self, buf: &[u8], index: u8) -> bool {
+        let index = index as usize;
+        let Some(&value) = buf.get(index) else {
+            return false;
+        };
+        match self {
+            ButtonQuery::Bit(mask) | ButtonQuery::BitPressure(mask, _) => (value & mask) != 0,
+            ButtonQuery::Eq(val) => value == *val,
+            ButtonQuery::Masked(mask, val) => (value & mask) == *val,
+            ButtonQuery::Range(min, max) => (*min..=*max).contains(&value),
+            ButtonQuery::In(values) => values.contains(&value),
+            ButtonQuery::Not(queries) => !queries.iter().any(|q| q.matches(buf, index as u8)),
+            ButtonQuery::Bit16(mask) => buf
+                .get(index..index + 2)
+                .is_some_and(|b| u16::from_le_bytes([b[0], b[1]]) & mask != 0),
+            ButtonQuery::Bit32(mask) => buf
+                .get(index..index + 4)
+                .is_some_and(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) & mask != 0),
+            ButtonQuery::Analog(released, pressed) => {
+                if released <= pressed {
+                    value > *released
+                } else {
+                    value < *released
+                }
+            }
+        }
+    }
+
+    /// The number of bytes the query reads.
+    pub fn width(&self) -> u8 {
+        match self {
+            ButtonQuery::Not(queries) => queries.iter().map(|q| q.width()).max().unwrap_or(1),
+            ButtonQuery::Bit16(_) => 2,
+            ButtonQuery::Bit32(_) => 4,
+            _ => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_button_axis_ramp() {
+        let ptr = ButtonAxisPointer {
+            negative: ButtonPointer(0, ButtonQuery::Bit(1)),
+            positive: ButtonPointer(0, ButtonQuery::Bit(2)),
+            ramp: Some(2.0),
+        };
+        let held = [2u8];
+        let value = ptr.step(0.0, &held, 0.25);
+        assert_eq!(value, 0.5);
+        assert_eq!(ptr.step(value, &held, 0.5), 1.0);
+        assert_eq!(ptr.step(1.0, &[1u8], 0.25), -0.5);
+        assert_eq!(ptr.step(1.0, &[0u8], 0.25), 0.0);
+    }
+
+    #[test]
+    fn test_button_queries() {
+        let buf = [0x35, 0x00, 0x01];
+        assert!(ButtonQuery::Masked(0xF0, 0x30).matches(&buf, 0));
+        assert!(ButtonQuery::Range(0x30, 0x3F).matches(&buf, 0));
+        assert!(ButtonQuery::In(vec![1, 0x35]).matches(&buf, 0));
+        assert!(ButtonQuery::Not(vec![ButtonQuery::Eq(0)]).matches(&buf, 0));
+        assert!(ButtonQuery::Bit16(0x0100).matches(&buf, 1));
+        assert!(!ButtonQuery::Bit32(0x0100).matches(&buf, 1));
+        assert_eq!(
+            ButtonPointer(1, ButtonQuery::Bit16(1))
+                .indices()
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn test_analog_button() {
+        let ptr = ButtonPointer(0, ButtonQuery::Analog(10, 210));
+        assert_eq!(ptr.value(&[0]), 0.0);
+        assert!(!ptr.pressed(&[10]));
+        assert_eq!(ptr.value(&[110]), 0.5);
+        assert!(ptr.pressed(&[110]));
+        assert_eq!(ptr.value(&[255]), 1.0);
+        assert_eq!(ButtonPointer(0, ButtonQuery::Bit(1)).value(&[3]), 1.0);
+
+        let pressure = PressurePointer {
+            index: 1,
+            released: 0,
+            pressed: 200,
+        };
+        let ptr = ButtonPointer(0, ButtonQuery::BitPressure(4, pressure));
+        assert_eq!(ptr.value(&[0, 100]), 0.0);
+        assert_eq!(ptr.value(&[4, 100]), 0.5);
+        assert_eq!(ptr.indices().collect::<Vec<_>>(), [0, 1]);
+    }
+
+    #[test]
+    fn test_relative_delta() {
+        let counter = RelativePointer {
+            index: 0,
+            encoding: RelativeEncoding::Counter,
+            bytes: None,
+            increment: None,
+            decrement: None,
+        };
+        assert_eq!(counter.delta(&[2], &[254]), 4);
+        assert_eq!(counter.delta(&[254], &[2]), -4);
+
+        let delta = RelativePointer {
+            encoding: RelativeEncoding::Delta,
+            bytes: Some(2),
+            ..counter
+        };
+        assert_eq!(delta.delta(&[0xFE, 0xFF], &[0, 0]), -2);
+        assert_eq!(delta.delta(&[0x00, 0x01], &[0, 0]), 256);
+    }
+
+    #[test]
+    fn test_switch_position() {
+        let switch = SwitchPointer {
+            positions: vec![
+                ("up".to_string(), ButtonPointer(0, ButtonQuery::Bit(1))),
+                ("down".to_string(), ButtonPointer(0, ButtonQuery::Bit(2))),
+            ],
+            default: Some("center".to_string()),
+        };
+        assert_eq!(switch.position(&[1]), Some("up"));
+        assert_eq!(switch.position(&[2]), Some("down"));
+        assert_eq!(switch.position(&[0]), Some("center"));
+    }
+
+    #[test]
+    fn test_axis_shape() {
+        let ptr = AxisPointer {
+            deadzone: Some(0.25),
+            saturation: Some(0.75),
+            curve: ResponseCurve::Spline(vec![(0.5, 0.25)]),
+            ..Default::default()
+        };
+        assert_eq!(ptr.shape(0.2), 0.0);
+        assert_eq!(ptr.shape(-0.5), -0.25);
+        assert_eq!(ptr.shape(0.625), 0.625);
+        assert_eq!(ptr.shape(0.8), 1.0);
+    }
+
+    #[test]
+    fn test_axis_signed_encoding() {
+        let signed = AxisPointer {
+            encoding: Some(AxisEncoding::Signed),
+            coarse: 1,
+            ..Default::default()
+        };
+        assert_eq!(signed.normalize(&[0, 0], None), 0.0);
+        assert_eq!(signed.normalize(&[0x00, 0x80], None), -1.0);
+        assert_eq!(signed.normalize(&[0xFF, 0x7F], None), 1.0);
+    }
+
+    #[test]
+    fn test_stick_radial_deadzone() {
+        let stick = StickPair {
+            x: GamepadAxis::LeftStickX,
+            y: GamepadAxis::LeftStickY,
+            deadzone: Some(0.25),
+            saturation: None,
+        };
+        // A diagonal that would escape a square deadzone stays centered.
+        assert_eq!(stick.apply(0.15, 0.15), (0.0, 0.0));
+        assert_eq!(stick.apply(0.0, -0.625), (0.0, -0.5));
+        assert_eq!(stick.apply(0.6, 0.8), (0.6, 0.8));
+    }
 }
@@ -0,0 +1,92 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    assets::HidAsset,
+    bindings::{Binding, BindingLabel},
+    components::HidDevice,
+    resources::HidLocale,
+};
+
+/// Looks up display names for the inputs of connected hids, in the active [`HidLocale`], or in the locale their
+/// asset was loaded with while that is unset.
+#[derive(SystemParam)]
+pub struct HidLabels<'w, 's> {
+    devices: Query<'w, 's, &'static HidDevice>,
+    assets: Res<'w, Assets<HidAsset>>,
+    locale: Res<'w, HidLocale>,
+}
+
+impl HidLabels<'_, '_> {
+    /// Gets the label metadata for an input on a gamepad, if the gamepad is a hid and the input has a label.
+    pub fn get(&self, gamepad: Entity, binding: impl Into<Binding>) -> Option<&BindingLabel> {
+        self.resolve(gamepad, binding).map(|(label, _)| label)
+    }
+
+    /// The localized name of an input on a gamepad.
+    pub fn label(&self, gamepad: Entity, binding: impl Into<Binding>) -> Option<&str> {
+        self.resolve(gamepad, binding)
+            .map(|(label, locale)| label.label_in(locale))
+    }
+
+    /// The localized short name of an input on a gamepad.
+    pub fn short_label(&self, gamepad: Entity, binding: impl Into<Binding>) -> Option<&str> {
+        self.resolve(gamepad, binding)
+            .map(|(label, locale)| label.short_in(locale))
+    }
+
+    /// Finds an input's label and the locale to show it in. The asset's own locale is used while [`HidLocale`]
+    /// is unset.
+    fn resolve(
+        &self,
+        gamepad: Entity,
+        binding: impl Into<Binding>,
+    ) -> Option<(&BindingLabel, Option<&str>)> {
+        let device = self.devices.get(gamepad).ok()?;
+        let asset = self.assets.get(device.asset())?;
+        let locale = self.locale.0.as_deref().or(asset.locale());
+        Some((asset.label_for(binding)?, locale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{device::DeviceId, util::load_raw_devices};
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn test_asset_locale_fallback() {
+        let throttle = load_raw_devices()
+            .into_iter()
+            .find(|device| device.name() == "TWCS Throttle (HID)")
+            .unwrap()
+            .with_locale("de");
+        let mut assets = Assets::<HidAsset>::default();
+        let asset = assets.add(HidAsset::from(&throttle));
+
+        let mut app = App::new();
+        app.insert_resource(assets).init_resource::<HidLocale>();
+        let gamepad = app
+            .world_mut()
+            .spawn(HidDevice {
+                id: DeviceId::new(1, 2),
+                asset,
+            })
+            .id();
+        let pinky = GamepadButton::Other(2);
+        let labels = move |labels: HidLabels| {
+            let label = labels.label(gamepad, pinky).map(str::to_string);
+            let short = labels.short_label(gamepad, pinky).map(str::to_string);
+            (label, short)
+        };
+
+        let (label, short) = app.world_mut().run_system_once(labels).unwrap();
+        assert_eq!(label.as_deref(), Some("Kleiner-Finger-Taste"));
+        assert_eq!(short.as_deref(), Some("Klein"));
+
+        // A locale chosen at runtime takes over from the asset's.
+        app.insert_resource(HidLocale(Some("en".to_string())));
+        let (label, _) = app.world_mut().run_system_once(labels).unwrap();
+        assert_eq!(label.as_deref(), Some("Pinky button"));
+    }
+}
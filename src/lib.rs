@@ -1,12 +1,23 @@
+mod formats;
 mod plugin;
+mod serialization;
 
 pub mod assets;
 pub mod bindings;
 pub mod buffers;
+pub mod builder;
+pub mod components;
 pub mod device;
+#[cfg(feature = "embedded")]
+pub mod embedded;
+pub mod events;
 pub mod input;
+pub mod labels;
+pub mod mappings;
+pub mod migration;
 pub mod resources;
 pub mod systems;
 pub mod util;
+pub mod validation;
 
 pub use plugin::HidPlugin;
@@ -0,0 +1,260 @@
+use bevy::{
+    ecs::system::SystemParam,
+    input::gamepad::{GamepadConnection, GamepadConnectionEvent},
+    prelude::*,
+};
+
+use crate::{
+    assets::HidAsset,
+    buffers::{ButtonAxisState, ChordState, HidBuffer, PulseState},
+    components::{HidAxisZones, HidDevice, HidSwitches},
+    device::DeviceAsset,
+    resources::HumanInterfaceDevices,
+    systems::{GamepadWriters, HidHeldState},
+};
+
+/// Registers, replaces and unregisters device mappings at runtime. Changes apply to connected devices straight away:
+/// devices that change mapping are reset as if they had just connected, and devices left without one are disconnected.
+///
+/// Device assets loaded through the [`AssetServer`] are registered automatically; this is for mappings built in code,
+/// downloaded or read from settings.
+#[derive(SystemParam)]
+pub struct HidMappings<'w, 's> {
+    commands: Commands<'w, 's>,
+    resources: ResMut<'w, HumanInterfaceDevices>,
+    assets: ResMut<'w, Assets<HidAsset>>,
+    gamepads: Query<'w, 's, (Entity, &'static mut HidDevice, Option<HidHeldState>)>,
+    writers: GamepadWriters<'w>,
+}
+
+impl HidMappings<'_, '_> {
+    /// Registers a mapping, taking precedence over every mapping registered before it.
+    /// The returned handle identifies the mapping when replacing or unregistering it.
+    pub fn register(&mut self, device: &DeviceAsset) -> Handle<HidAsset> {
+        let handle = self.assets.add(HidAsset::from(device));
+        self.resources
+            .register(None, None, device.ids().to_vec(), handle.clone());
+        self.refresh(&handle, None);
+        handle
+    }
+
+    /// Replaces a registered mapping, keeping its precedence. Returns false if it is not registered.
+    pub fn replace(&mut self, handle: &Handle<HidAsset>, device: &DeviceAsset) -> bool {
+        let Some(source) = self
+            .resources
+            .sources
+            .iter()
+            .find(|mapping| &mapping.handle == handle)
+            .map(|mapping| mapping.source)
+        else {
+            return false;
+        };
+        let old = self.assets.get(handle).cloned();
+        if self.assets.insert(handle, HidAsset::from(device)).is_err() {
+            return false;
+        }
+        self.resources
+            .register(source, None, device.ids().to_vec(), handle.clone());
+        self.refresh(handle, old.as_ref());
+        true
+    }
+
+    /// Unregisters a mapping. Returns false if it was not registered.
+    pub fn unregister(&mut self, handle: &Handle<HidAsset>) -> bool {
+        if !self.resources.unregister(handle) {
+            return false;
+        }
+        self.refresh(handle, None);
+        true
+    }
+
+    /// Registers or replaces the mapping built from a device asset loaded from one of the asset folders.
+    pub(crate) fn sync_asset(
+        &mut self,
+        source: AssetId<DeviceAsset>,
+        folder: usize,
+        device: &DeviceAsset,
+    ) {
+        let (handle, old) = match self.resources.source(source) {
+            Some(handle) => {
+                let handle = handle.clone();
+                let old = self.assets.get(&handle).cloned();
+                // Replacing the asset in place keeps every handle to it valid.
+                if self.assets.insert(&handle, HidAsset::from(device)).is_err() {
+                    return;
+                }
+                (handle, old)
+            }
+            None => (self.assets.add(HidAsset::from(device)), None),
+        };
+        self.resources.register(
+            Some(source),
+            Some(folder),
+            device.ids().to_vec(),
+            handle.clone(),
+        );
+        self.refresh(&handle, old.as_ref());
+    }
+
+    /// Moves connected devices onto their current mapping. Devices using `changed` are reset even if their
+    /// mapping is the same, as their state was read with the old bindings; `old` is what `changed` held before.
+    /// Anything a device is holding is released before its state is reset.
+    fn refresh(&mut self, changed: &Handle<HidAsset>, old: Option<&HidAsset>) {
+        for (gamepad, mut hid, held) in self.gamepads.iter_mut() {
+            let handle = self.resources.asset_for(&hid.id).cloned();
+            if handle.as_ref() == Some(&hid.asset) && &hid.asset != changed {
+                continue;
+            }
+            let previous = old
+                .filter(|_| &hid.asset == changed)
+                .or_else(|| self.assets.get(&hid.asset));
+            if let (Some(previous), Some(held)) = (previous, held) {
+                self.writers.release(gamepad, previous, held);
+            }
+            match handle {
+                Some(handle) => {
+                    self.commands.entity(gamepad).insert((
+                        HidBuffer::default(),
+                        ButtonAxisState::default(),
+                        ChordState::default(),
+                        PulseState::default(),
+                        HidSwitches::default(),
+                        HidAxisZones::default(),
+                    ));
+                    // Announce the device again under its new mapping's name.
+                    if let Some(asset) = self.assets.get(&handle)
+                        && (handle != hid.asset
+                            || previous.is_none_or(|previous| previous.name() != asset.name()))
+                    {
+                        self.writers.connected(gamepad, asset, hid.id);
+                    }
+                    hid.asset = handle;
+                }
+                // The gamepad entity is kept, like bevy does for disconnected gamepads, so it is picked up again
+                // if a mapping for the device is registered later.
+                None => {
+                    self.commands.entity(gamepad).remove::<(
+                        HidDevice,
+                        HidBuffer,
+                        ButtonAxisState,
+                        ChordState,
+                        PulseState,
+                        HidSwitches,
+                        HidAxisZones,
+                    )>();
+                    self.writers.connection(GamepadConnectionEvent::new(
+                        gamepad,
+                        GamepadConnection::Disconnected,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        device::{DeviceId, DeviceMap},
+        events::{HidAxisZoneChanged, HidRelativeAxisMoved, HidSwitchChanged},
+        input::{ButtonPointer, ButtonQuery},
+    };
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        input::gamepad::{
+            RawGamepadAxisChangedEvent, RawGamepadButtonChangedEvent, RawGamepadEvent,
+        },
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_register_and_unregister() {
+        let mut app = App::new();
+        app.init_resource::<HumanInterfaceDevices>()
+            .init_resource::<Assets<HidAsset>>()
+            .add_message::<RawGamepadEvent>()
+            .add_message::<GamepadConnectionEvent>()
+            .add_message::<RawGamepadButtonChangedEvent>()
+            .add_message::<RawGamepadAxisChangedEvent>()
+            .add_message::<HidRelativeAxisMoved>()
+            .add_message::<HidSwitchChanged>()
+            .add_message::<HidAxisZoneChanged>();
+
+        let buttons =
+            HashMap::from([(GamepadButton::South, ButtonPointer(0, ButtonQuery::Bit(1)))]);
+        let map = DeviceMap::new(buttons, HashMap::new());
+        let device = DeviceAsset::new("Pad".to_string(), 1, 2, map);
+        let handle = app
+            .world_mut()
+            .run_system_once(move |mut mappings: HidMappings| mappings.register(&device))
+            .unwrap();
+
+        let id = DeviceId::new(2, 1);
+        let gamepad = app
+            .world_mut()
+            .spawn((
+                HidDevice {
+                    id,
+                    asset: handle.clone(),
+                },
+                HidBuffer([1; 256], true),
+                ButtonAxisState::default(),
+                ChordState::default(),
+            ))
+            .id();
+
+        let override_device = DeviceAsset::new(
+            "Pad v2".to_string(),
+            1,
+            2,
+            DeviceMap::new(HashMap::new(), HashMap::new()),
+        );
+        let replacement = app
+            .world_mut()
+            .run_system_once(move |mut mappings: HidMappings| mappings.register(&override_device))
+            .unwrap();
+        let hid = app.world().get::<HidDevice>(gamepad).unwrap();
+        assert_eq!(hid.asset, replacement);
+        // The held button is released before the state is reset.
+        let released = app
+            .world_mut()
+            .resource_mut::<Messages<RawGamepadButtonChangedEvent>>()
+            .drain()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            released,
+            [RawGamepadButtonChangedEvent::new(
+                gamepad,
+                GamepadButton::South,
+                0.0
+            )]
+        );
+        let connected = app
+            .world_mut()
+            .resource_mut::<Messages<GamepadConnectionEvent>>()
+            .drain()
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            &connected[..],
+            [GamepadConnectionEvent {
+                connection: GamepadConnection::Connected { name, .. },
+                ..
+            }] if name == "Pad v2"
+        ));
+        assert_eq!(
+            *app.world().get::<HidBuffer>(gamepad).unwrap(),
+            HidBuffer::default()
+        );
+
+        let unregister = replacement.clone();
+        app.world_mut()
+            .run_system_once(move |mut mappings: HidMappings| {
+                assert!(mappings.unregister(&unregister));
+                assert!(mappings.unregister(&handle));
+            })
+            .unwrap();
+        assert!(app.world().get::<HidDevice>(gamepad).is_none());
+        assert!(app.world().get_entity(gamepad).is_ok());
+    }
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, de::IgnoredAny};
+use std::path::Path;
+
+#[cfg(feature = "json")]
+use crate::formats::Json;
+#[cfg(feature = "toml")]
+use crate::formats::Toml;
+use crate::{
+    assets::HidAssetLoadError,
+    device::{DeviceAsset, DeviceMap, DeviceMatch},
+    formats::{AssetFormat, Ron},
+};
+
+/// The current version of the `.hid.ron` schema.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Just enough of an asset to find out which schema it was written against.
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default)]
+    version: u32,
+    /// Present when an unversioned asset is already written in the current layout.
+    #[serde(default)]
+    ids: Option<IgnoredAny>,
+}
+
+/// Versions 0 and 1 identified a device by a single `pid` and `vid`.
+#[derive(Deserialize)]
+struct DeviceAssetV1 {
+    name: String,
+    pid: u16,
+    vid: u16,
+    #[serde(default)]
+    base: Option<String>,
+    mappings: DeviceMap,
+}
+
+impl From<DeviceAssetV1> for DeviceAsset {
+    fn from(asset: DeviceAssetV1) -> Self {
+        DeviceAsset {
+            version: 1,
+            name: asset.name,
+            ids: vec![DeviceMatch::Id {
+                vid: asset.vid,
+                pid: asset.pid,
+            }],
+            base: asset.base,
+            mappings: asset.mappings,
+            locale: None,
+        }
+    }
+}
+
+/// Reads a `.hid.ron` device asset written against any supported schema version and upgrades it to [`SCHEMA_VERSION`].
+/// Deprecated constructs found along the way are returned as warnings.
+pub fn migrate(bytes: &[u8]) -> Result<(DeviceAsset, Vec<String>), HidAssetLoadError> {
+    migrate_from::<Ron>(bytes)
+}
+
+/// Like [`migrate`], picking the format from the extension of `path`.
+pub(crate) fn migrate_path(
+    path: &Path,
+    bytes: &[u8],
+) -> Result<(DeviceAsset, Vec<String>), HidAssetLoadError> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        #[cfg(feature = "json")]
+        Some("json") => migrate_from::<Json>(bytes),
+        #[cfg(feature = "toml")]
+        Some("toml") => migrate_from::<Toml>(bytes),
+        _ => migrate_from::<Ron>(bytes),
+    }
+}
+
+/// Like [`migrate`], for an asset in any supported format.
+pub(crate) fn migrate_from<F: AssetFormat>(
+    bytes: &[u8],
+) -> Result<(DeviceAsset, Vec<String>), HidAssetLoadError> {
+    let VersionProbe { mut version, ids } = F::parse(bytes)?;
+    if version > SCHEMA_VERSION {
+        return Err(HidAssetLoadError::UnsupportedVersion(version));
+    }
+
+    let mut warnings = Vec::new();
+
+    // Version 0: assets written before the schema was versioned. The layout matches version 1, unless the asset
+    // already has an `ids` list, in which case it was written against the current schema and just left out the version.
+    if version == 0 && ids.is_some() {
+        warnings.push(format!(
+            "asset has no `version` field; add `{}`",
+            F::version_field(SCHEMA_VERSION)
+        ));
+        version = SCHEMA_VERSION;
+    } else if version == 0 {
+        warnings.push(format!(
+            "asset has no `version` field and is assumed to predate versioning; add `{}`",
+            F::version_field(SCHEMA_VERSION)
+        ));
+    }
+
+    // Version 1: a single `pid` and `vid`, replaced by the `ids` list in version 2.
+    let mut asset: DeviceAsset = if version <= 1 {
+        let legacy: DeviceAssetV1 = F::parse(bytes)?;
+        warnings.push(format!(
+            "`pid` and `vid` are deprecated, use `{}` instead",
+            F::ids_field(legacy.vid, legacy.pid)
+        ));
+        legacy.into()
+    } else {
+        F::parse(bytes)?
+    };
+
+    asset.version = SCHEMA_VERSION;
+    Ok((asset, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_versions() {
+        let legacy = br#"(name: "Pad", pid: 1, vid: 2, mappings: (buttons: {}, axes: {}))"#;
+        let (asset, warnings) = migrate(legacy).unwrap();
+        assert_eq!(asset.version(), SCHEMA_VERSION);
+        assert_eq!(asset.ids(), &[DeviceMatch::Id { vid: 2, pid: 1 }]);
+        assert_eq!(warnings.len(), 2);
+
+        let v1 = br#"(version: 1, name: "Pad", pid: 1, vid: 2, mappings: (buttons: {}, axes: {}))"#;
+        let (asset, warnings) = migrate(v1).unwrap();
+        assert_eq!(asset.ids(), &[DeviceMatch::Id { vid: 2, pid: 1 }]);
+        assert_eq!(warnings.len(), 1);
+        #[allow(deprecated)]
+        let (vid, pid) = (asset.vid(), asset.pid());
+        assert_eq!((vid, pid), (2, 1));
+
+        let unversioned =
+            br#"(name: "Pad", ids: [Id(vid: 2, pid: 1)], mappings: (buttons: {}, axes: {}))"#;
+        let (asset, warnings) = migrate(unversioned).unwrap();
+        assert_eq!(asset.ids(), &[DeviceMatch::Id { vid: 2, pid: 1 }]);
+        assert_eq!(warnings, ["asset has no `version` field; add `version: 2`"]);
+
+        let current =
+            br#"(version: 2, name: "Pad", ids: [Id(vid: 2, pid: 1), Range(vid: 2, min: 8, max: 9)],
+            mappings: (buttons: {}, axes: {}))"#;
+        let (asset, warnings) = migrate(current).unwrap();
+        assert!(warnings.is_empty());
+        assert!(asset.matches(&crate::device::DeviceId::new(2, 9)));
+
+        let future =
+            br#"(version: 99, name: "Pad", pid: 1, vid: 2, mappings: (buttons: {}, axes: {}))"#;
+        assert!(matches!(
+            migrate(future),
+            Err(HidAssetLoadError::UnsupportedVersion(99))
+        ));
+    }
+}
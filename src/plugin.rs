@@ -1,35 +1,110 @@
-use bevy::prelude::*;
+use bevy::{
+    input::gamepad::{
+        GamepadConnectionEvent, RawGamepadAxisChangedEvent, RawGamepadButtonChangedEvent,
+        RawGamepadEvent,
+    },
+    prelude::*,
+};
 
+#[cfg(feature = "embedded")]
+use crate::embedded::{EMBEDDED_FOLDER, EmbeddedDevices, register_embedded_devices};
 use crate::{
-    assets::{HidAsset, HidAssetLoader, TemporaryDeviceAssets},
+    assets::{HidAsset, HidAssetFolders, HidAssetLoader, TemporaryDeviceAssets},
+    components::{HidAxisZones, HidCalibration, HidDevice, HidSwitches},
     device::DeviceAsset,
-    resources::{HidApi, HumanInterfaceDevices},
+    events::{HidAxisZoneChanged, HidRelativeAxisMoved, HidSwitchChanged},
+    resources::{HidApi, HidLocale, HumanInterfaceDevices},
     systems::*,
 };
 
-pub struct HidPlugin;
+/// Reads hid devices and translates their reports into bevy gamepad input.
+///
+/// This is no longer a unit struct: add it with `HidPlugin::default()` rather than `HidPlugin`.
+pub struct HidPlugin {
+    /// Asset folders to load device mappings from. These can name other asset sources, such as `user://hid`.
+    /// When mappings in several folders claim the same device, the one from the later folder is used.
+    pub folders: Vec<String>,
+    /// The bundled mappings to register. These are loaded before any of the `folders`, so they can be overridden.
+    #[cfg(feature = "embedded")]
+    pub embedded: EmbeddedDevices,
+}
+
+impl Default for HidPlugin {
+    fn default() -> Self {
+        Self {
+            folders: vec!["hid".to_string()],
+            #[cfg(feature = "embedded")]
+            embedded: EmbeddedDevices::default(),
+        }
+    }
+}
+
+impl HidPlugin {
+    /// Adds a folder of mappings that override those from the folders before it.
+    pub fn with_folder(mut self, folder: impl Into<String>) -> Self {
+        self.folders.push(folder.into());
+        self
+    }
+
+    /// Selects which of the bundled mappings are registered.
+    #[cfg(feature = "embedded")]
+    pub fn with_embedded(mut self, embedded: EmbeddedDevices) -> Self {
+        self.embedded = embedded;
+        self
+    }
+}
 
 impl Plugin for HidPlugin {
     fn build(&self, app: &mut App) {
+        #[allow(unused_mut)]
+        let mut folders = self.folders.clone();
+        #[cfg(feature = "embedded")]
+        if register_embedded_devices(app, &self.embedded) {
+            folders.insert(0, EMBEDDED_FOLDER.to_string());
+        }
+
+        app.insert_resource(HidAssetFolders(folders))
+            .init_resource::<HumanInterfaceDevices>()
+            .register_type::<HumanInterfaceDevices>()
+            .init_resource::<HidLocale>()
+            .register_type::<HidLocale>()
+            .register_type::<HidDevice>()
+            .register_type::<HidAsset>()
+            .register_type::<HidCalibration>()
+            .register_type::<HidSwitches>()
+            .register_type::<HidAxisZones>()
+            .init_asset::<DeviceAsset>()
+            .init_asset::<HidAsset>()
+            .register_asset_reflect::<HidAsset>()
+            .init_asset_loader::<HidAssetLoader>()
+            .add_message::<RawGamepadEvent>()
+            .add_message::<GamepadConnectionEvent>()
+            .add_message::<RawGamepadButtonChangedEvent>()
+            .add_message::<RawGamepadAxisChangedEvent>()
+            .add_message::<HidRelativeAxisMoved>()
+            .add_message::<HidSwitchChanged>()
+            .add_message::<HidAxisZoneChanged>()
+            .add_systems(PreStartup, load_device_assets)
+            .add_systems(
+                PreUpdate,
+                // Devices are read only after mapping changes, and the entities they touch, have been applied.
+                (
+                    check_device_assets_loaded.run_if(resource_exists::<TemporaryDeviceAssets>),
+                    reload_device_assets.run_if(not(resource_exists::<TemporaryDeviceAssets>)),
+                    ApplyDeferred,
+                    update_hid_devices.run_if(resource_exists::<HidApi>),
+                )
+                    .chain(),
+            );
+        #[cfg(feature = "json")]
+        app.init_asset_loader::<HidAssetLoader<crate::formats::Json>>();
+        #[cfg(feature = "toml")]
+        app.init_asset_loader::<HidAssetLoader<crate::formats::Toml>>();
+
+        // Mappings can still be registered and looked up without the api, only the devices are not read.
         match HidApi::new() {
             Ok(api) => {
-                app.insert_resource(api)
-                    .init_resource::<HumanInterfaceDevices>()
-                    .register_type::<HumanInterfaceDevices>()
-                    .register_type::<HidAsset>()
-                    .init_asset::<DeviceAsset>()
-                    .init_asset::<HidAsset>()
-                    .register_asset_reflect::<HidAsset>()
-                    .init_asset_loader::<HidAssetLoader>()
-                    .add_systems(PreStartup, load_device_assets)
-                    .add_systems(
-                        PreUpdate,
-                        (
-                            check_device_assets_loaded
-                                .run_if(resource_exists::<TemporaryDeviceAssets>),
-                            update_hid_devices.run_if(resource_exists::<HumanInterfaceDevices>),
-                        ),
-                    );
+                app.insert_resource(api);
             }
             Err(err) => error!("Failed to start Hid client: {}", err),
         }
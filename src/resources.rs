@@ -3,7 +3,10 @@ use bevy::prelude::*;
 use hidapi::HidResult;
 use std::collections::HashMap;
 
-use crate::{assets::HidAsset, device::DeviceId};
+use crate::{
+    assets::HidAsset,
+    device::{DeviceAsset, DeviceId, DeviceMatch},
+};
 
 #[derive(Resource)]
 pub(crate) struct HidApi {
@@ -12,10 +15,28 @@ pub(crate) struct HidApi {
 
 #[derive(Clone, Resource, Default, Reflect)]
 pub struct HumanInterfaceDevices {
-    pub(crate) assets: HashMap<DeviceId, Handle<HidAsset>>,
     pub(crate) connected: HashMap<String, Entity>,
+    /// Every registered mapping, the device asset it was built from (if any) and the devices it matches,
+    /// in registration order.
+    pub(crate) sources: Vec<RegisteredMapping>,
 }
 
+#[derive(Clone, Reflect)]
+pub(crate) struct RegisteredMapping {
+    pub(crate) source: Option<AssetId<DeviceAsset>>,
+    /// The index of the asset folder the mapping was loaded from. Mappings registered in code have none.
+    pub(crate) folder: Option<usize>,
+    pub(crate) ids: Vec<DeviceMatch>,
+    pub(crate) handle: Handle<HidAsset>,
+}
+
+/// The locale used when looking up binding labels through [`HidLabels`]. With no locale set,
+/// the untranslated labels from the device assets are used.
+///
+/// [`HidLabels`]: crate::labels::HidLabels
+#[derive(Debug, Clone, Resource, Default, Reflect)]
+pub struct HidLocale(pub Option<String>);
+
 impl HidApi {
     pub(crate) fn new() -> HidResult<Self> {
         match hidapi::HidApi::new() {
@@ -31,15 +52,111 @@ impl HidApi {
 }
 
 impl HumanInterfaceDevices {
-    pub(crate) fn new(devices: HashMap<DeviceId, Handle<HidAsset>>) -> Self {
-        Self {
-            assets: devices,
-            connected: HashMap::new(),
+    /// Registers a mapping against the devices it matches. Registering the same mapping again updates it in place,
+    /// keeping its precedence and folder.
+    pub(crate) fn register(
+        &mut self,
+        source: Option<AssetId<DeviceAsset>>,
+        folder: Option<usize>,
+        ids: Vec<DeviceMatch>,
+        handle: Handle<HidAsset>,
+    ) {
+        match self.sources.iter_mut().find(|m| m.handle == handle) {
+            Some(entry) => {
+                entry.source = source;
+                entry.folder = folder.or(entry.folder);
+                entry.ids = ids;
+            }
+            None => self.sources.push(RegisteredMapping {
+                source,
+                folder,
+                ids,
+                handle,
+            }),
         }
     }
 
+    /// Removes a mapping. Returns false if it was not registered.
+    pub(crate) fn unregister(&mut self, handle: &Handle<HidAsset>) -> bool {
+        let len = self.sources.len();
+        self.sources.retain(|mapping| &mapping.handle != handle);
+        self.sources.len() != len
+    }
+
+    /// Gets the mapping built from a device asset.
+    pub(crate) fn source(&self, source: AssetId<DeviceAsset>) -> Option<&Handle<HidAsset>> {
+        self.sources
+            .iter()
+            .find(|mapping| mapping.source == Some(source))
+            .map(|mapping| &mapping.handle)
+    }
+
+    /// Whether a mapping is currently registered.
+    pub fn is_registered(&self, handle: &Handle<HidAsset>) -> bool {
+        self.sources.iter().any(|mapping| &mapping.handle == handle)
+    }
+
+    /// Finds the asset mapping a device. Mappings registered in code win over those from asset folders, and later
+    /// folders win over earlier ones. Within a folder exact ids win over ranges; otherwise the later mapping wins.
+    pub fn asset_for(&self, id: &DeviceId) -> Option<&Handle<HidAsset>> {
+        self.sources
+            .iter()
+            .enumerate()
+            .flat_map(|(index, mapping)| {
+                mapping
+                    .ids
+                    .iter()
+                    .filter(|m| m.matches(id))
+                    .map(move |m| (index, mapping, m))
+            })
+            .max_by_key(|(index, mapping, m)| {
+                let exact = matches!(m, DeviceMatch::Id { .. });
+                match mapping.folder {
+                    Some(folder) => (folder, exact, *index),
+                    None => (usize::MAX, false, *index),
+                }
+            })
+            .map(|(_, mapping, _)| &mapping.handle)
+    }
+
     /// Gets a list of all the connected devices.
     pub fn devices(&self) -> Vec<Entity> {
         self.connected.values().cloned().collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::DeviceMap;
+
+    #[test]
+    fn test_folder_precedence() {
+        let mut assets = Assets::<HidAsset>::default();
+        let mut handle = || {
+            let device = DeviceAsset::new("Pad".to_string(), 1, 2, DeviceMap::default());
+            assets.add(HidAsset::from(&device))
+        };
+        let (exact, range, later_range, runtime) = (handle(), handle(), handle(), handle());
+        let id = DeviceId::new(2, 1);
+        let exact_id = vec![DeviceMatch::Id { vid: 2, pid: 1 }];
+        let range_id = vec![DeviceMatch::Range {
+            vid: 2,
+            min: 0,
+            max: 9,
+        }];
+
+        let mut devices = HumanInterfaceDevices::default();
+        devices.register(None, Some(0), exact_id.clone(), exact.clone());
+        devices.register(None, Some(0), range_id.clone(), range);
+        assert_eq!(devices.asset_for(&id), Some(&exact));
+
+        devices.register(None, Some(1), range_id, later_range.clone());
+        assert_eq!(devices.asset_for(&id), Some(&later_range));
+
+        devices.register(None, None, exact_id, runtime.clone());
+        assert_eq!(devices.asset_for(&id), Some(&runtime));
+        devices.unregister(&runtime);
+        assert_eq!(devices.asset_for(&id), Some(&later_range));
+    }
+}
@@ -0,0 +1,82 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{MapAccess, SeqAccess, Visitor},
+    ser::SerializeMap,
+};
+
+/// Serializes a map in a stable order, so saved assets don't shuffle between writes.
+/// Keys are ordered by name, with any numbered variants (such as `Other(n)`) in numeric order.
+pub(crate) fn sorted_map<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Serialize + Debug,
+    V: Serialize,
+    S: Serializer,
+{
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by_cached_key(|(key, _)| natural_key(&format!("{key:?}")));
+
+    let mut state = serializer.serialize_map(Some(entries.len()))?;
+    for (key, value) in entries {
+        state.serialize_entry(key, value)?;
+    }
+    state.end()
+}
+
+/// Splits a debug name like `Other(12)` into `("Other", 12)`, keeping the full name to break ties.
+fn natural_key(name: &str) -> (String, u64, String) {
+    match name.rsplit_once('(') {
+        Some((variant, rest)) => match rest.trim_end_matches(')').parse() {
+            Ok(number) => (variant.to_string(), number, name.to_string()),
+            Err(_) => (name.to_string(), 0, name.to_string()),
+        },
+        None => (name.to_string(), 0, name.to_string()),
+    }
+}
+
+/// Deserializes a map written either as a map or as a list of `[key, value]` entries.
+/// JSON and TOML only allow string keys, so bindings like `Other(1)` have to be written as entries there.
+pub(crate) fn map_or_entries<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(MapOrEntries(PhantomData))
+}
+
+struct MapOrEntries<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> Visitor<'de> for MapOrEntries<K, V>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+{
+    type Value = HashMap<K, V>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map or a list of [key, value] entries")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        let mut map = HashMap::with_capacity(access.size_hint().unwrap_or(0));
+        while let Some((key, value)) = access.next_entry()? {
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        let mut map = HashMap::with_capacity(access.size_hint().unwrap_or(0));
+        while let Some((key, value)) = access.next_element()? {
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
@@ -1,14 +1,16 @@
-use std::collections::HashMap;
-
 use crate::{
-    assets::{HidAsset, TemporaryDeviceAssets},
-    buffers::{BufferMap, HidBuffer},
+    assets::{HidAsset, HidAssetFolders, TemporaryDeviceAssets},
+    buffers::{ButtonAxisState, ChordState, HidBuffer, PulseState},
+    components::{HidAxisZones, HidCalibration, HidDevice, HidSwitches},
     device::{DeviceAsset, DeviceId},
-    input::{AxisPointer, ButtonQuery},
+    events::{HidAxisZoneChanged, HidRelativeAxisMoved, HidSwitchChanged, ZoneTransition},
+    input::{AxisCalibration, AxisPointer, RelativeEncoding, StickPair},
+    mappings::HidMappings,
     resources::{HidApi, HumanInterfaceDevices},
 };
 use bevy::{
-    asset::LoadedFolder,
+    asset::{AssetPath, LoadedFolder},
+    ecs::{query::QueryData, system::SystemParam},
     input::gamepad::{
         GamepadConnection, GamepadConnectionEvent, RawGamepadAxisChangedEvent,
         RawGamepadButtonChangedEvent, RawGamepadEvent,
@@ -17,55 +19,235 @@ use bevy::{
 };
 
 /// Starts the initial loading sequence.
-pub(crate) fn load_device_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let device_assets = asset_server.load_folder("hid");
+pub(crate) fn load_device_assets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    folders: Res<HidAssetFolders>,
+) {
+    let mut device_assets = Vec::new();
+    for folder in &folders.0 {
+        let path = AssetPath::parse(folder);
+        // Folders on a missing source never finish loading, so they would hold up every other folder.
+        if asset_server.get_source(path.source()).is_err() {
+            warn!(
+                "Skipping hid folder {}: asset source does not exist",
+                folder
+            );
+            continue;
+        }
+        device_assets.push(asset_server.load_folder(path));
+    }
     commands.insert_resource(TemporaryDeviceAssets { device_assets });
 }
 
 /// Checks to see if all crate assets have been loaded and updates accordingly.
 pub(crate) fn check_device_assets_loaded(
     mut commands: Commands,
-    folder_handle: Option<Res<TemporaryDeviceAssets>>,
+    folder_handles: Option<Res<TemporaryDeviceAssets>>,
+    folders: Res<HidAssetFolders>,
+    asset_server: Res<AssetServer>,
     device_assets: Res<Assets<DeviceAsset>>,
-    mut mapped_assets: ResMut<Assets<HidAsset>>,
     loaded_folders: Res<Assets<LoadedFolder>>,
+    mut mappings: HidMappings,
 ) {
-    let Some(folder_handle) = folder_handle else {
+    let Some(folder_handles) = folder_handles else {
         return;
     };
 
-    if let Some(folder) = loaded_folders.get(&folder_handle.device_assets)
-        && !device_assets.is_empty()
-    {
-        let mut devices = HashMap::new();
+    let pending = folder_handles.device_assets.iter().any(|handle| {
+        let state = asset_server.recursive_dependency_load_state(handle);
+        !state.is_loaded() && !state.is_failed()
+    });
+    if pending {
+        return;
+    }
 
+    // Mappings registered in code while the folders were loading are kept, and still take precedence.
+    for handle in &folder_handles.device_assets {
+        let Some(folder) = loaded_folders.get(handle) else {
+            continue;
+        };
+        let Some(index) = asset_server
+            .get_path(handle)
+            .and_then(|path| folders.folder_of(&path))
+        else {
+            continue;
+        };
         for handle in &folder.handles {
             let typed_handle: Handle<DeviceAsset> = handle.clone().typed();
             if let Some(device) = device_assets.get(&typed_handle) {
-                let new_handle = mapped_assets.add(HidAsset {
-                    name: device.name().to_string(),
-                    input_mapping: device.mappings().clone(),
-                    buffer_map: BufferMap::from(device.mappings()),
-                });
-                devices.insert(DeviceId::from(device), new_handle);
+                mappings.sync_asset(typed_handle.id(), index, device);
             }
         }
+    }
+
+    commands.remove_resource::<TemporaryDeviceAssets>();
+}
+
+/// Rebuilds mappings when their device assets change on disk, and resets the gamepads using them.
+/// Device assets loaded from anywhere other than the mapping folders are left alone.
+pub(crate) fn reload_device_assets(
+    mut asset_events: MessageReader<AssetEvent<DeviceAsset>>,
+    asset_server: Res<AssetServer>,
+    folders: Res<HidAssetFolders>,
+    device_assets: Res<Assets<DeviceAsset>>,
+    mut mappings: HidMappings,
+) {
+    for event in asset_events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = *event else {
+            continue;
+        };
+        let Some(folder) = asset_server
+            .get_path(id)
+            .and_then(|path| folders.folder_of(&path))
+        else {
+            continue;
+        };
+        if let Some(device) = device_assets.get(id) {
+            debug!("Reloading mapping for {}", device.name());
+            mappings.sync_asset(id, folder, device);
+        }
+    }
+}
+
+/// Groups the gamepad message writers used when translating hid reports.
+#[derive(SystemParam)]
+pub(crate) struct GamepadWriters<'w> {
+    events: MessageWriter<'w, RawGamepadEvent>,
+    connection_events: MessageWriter<'w, GamepadConnectionEvent>,
+    button_events: MessageWriter<'w, RawGamepadButtonChangedEvent>,
+    axis_events: MessageWriter<'w, RawGamepadAxisChangedEvent>,
+    relative_events: MessageWriter<'w, HidRelativeAxisMoved>,
+    switch_events: MessageWriter<'w, HidSwitchChanged>,
+    zone_events: MessageWriter<'w, HidAxisZoneChanged>,
+}
+
+impl GamepadWriters<'_> {
+    pub(crate) fn connection(&mut self, event: GamepadConnectionEvent) {
+        self.events.write(event.clone().into());
+        self.connection_events.write(event);
+    }
+
+    /// Announces a hid as connected under the name of its mapping.
+    pub(crate) fn connected(&mut self, gamepad: Entity, asset: &HidAsset, id: DeviceId) {
+        self.connection(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected {
+                name: asset.name().to_string(),
+                vendor_id: Some(id.vendor_id),
+                product_id: Some(id.product_id),
+            },
+        ));
+    }
+
+    fn button(&mut self, gamepad: Entity, button: GamepadButton, value: f32) {
+        let event = RawGamepadButtonChangedEvent::new(gamepad, button, value);
+        self.events.write(event.into());
+        self.button_events.write(event);
+    }
 
-        commands.insert_resource(HumanInterfaceDevices::new(devices));
-        commands.remove_resource::<TemporaryDeviceAssets>();
+    fn axis(&mut self, gamepad: Entity, axis: GamepadAxis, value: f32) {
+        let event = RawGamepadAxisChangedEvent::new(gamepad, axis, value);
+        self.events.write(event.into());
+        self.axis_events.write(event);
+    }
+
+    fn relative(&mut self, gamepad: Entity, axis: GamepadAxis, delta: i32) {
+        self.relative_events
+            .write(HidRelativeAxisMoved::new(gamepad, axis, delta));
+    }
+
+    fn switch(
+        &mut self,
+        gamepad: Entity,
+        switch: &str,
+        position: Option<String>,
+        previous: Option<String>,
+    ) {
+        self.switch_events.write(HidSwitchChanged {
+            gamepad,
+            switch: switch.to_string(),
+            position,
+            previous,
+        });
+    }
+
+    fn zone(&mut self, gamepad: Entity, axis: GamepadAxis, zone: &str, transition: ZoneTransition) {
+        self.zone_events.write(HidAxisZoneChanged {
+            gamepad,
+            axis,
+            zone: zone.to_string(),
+            transition,
+        });
+    }
+
+    /// Presses and releases a button within the same frame.
+    fn pulse(&mut self, gamepad: Entity, button: GamepadButton) {
+        self.button(gamepad, button, 1.0);
+        self.button(gamepad, button, 0.0);
+    }
+
+    /// Releases every button and recenters every axis a hid is holding under `asset`, so nothing stays stuck
+    /// once its state is reset.
+    pub(crate) fn release(&mut self, gamepad: Entity, asset: &HidAsset, held: HidHeldStateItem) {
+        let map = &asset.input_mapping;
+        let HidBuffer(buf, seeded) = held.buffer;
+        // Buttons and axes are only written once a report comes in.
+        if *seeded {
+            let layer = map.active_layer(buf);
+            for (button, ptr) in &map.buttons {
+                if ptr.value(buf) != 0.0 && !held.chords.suppressed.contains(button) {
+                    self.button(gamepad, map.resolve(*button, layer), 0.0);
+                }
+            }
+            for (axis, ptr) in &map.axes {
+                let calibration = held.calibration.and_then(|c| c.get(*axis));
+                if ptr.value(buf, calibration) != 0.0 {
+                    self.axis(gamepad, *axis, 0.0);
+                }
+            }
+        }
+        for button in &held.chords.active {
+            self.button(gamepad, *button, 0.0);
+        }
+        for (axis, value) in &held.button_axes.0 {
+            if *value != 0.0 {
+                self.axis(gamepad, *axis, 0.0);
+            }
+        }
     }
 }
 
+/// What a hid is currently holding, read when its mapping changes.
+#[derive(QueryData)]
+pub(crate) struct HidHeldState {
+    buffer: &'static HidBuffer,
+    button_axes: &'static ButtonAxisState,
+    chords: &'static ChordState,
+    calibration: Option<&'static HidCalibration>,
+}
+
+/// The state a connected hid keeps on its gamepad entity.
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct HidDeviceState {
+    buffer: &'static mut HidBuffer,
+    button_axes: &'static mut ButtonAxisState,
+    chords: &'static mut ChordState,
+    pulses: &'static mut PulseState,
+    switches: &'static mut HidSwitches,
+    zones: &'static mut HidAxisZones,
+    calibration: Option<&'static HidCalibration>,
+}
+
 pub(crate) fn update_hid_devices(
     mut commands: Commands,
     mut hid: ResMut<HidApi>,
     mut resources: ResMut<HumanInterfaceDevices>,
-    mut gamepads: Query<&mut HidBuffer, With<Gamepad>>,
-    mut events: MessageWriter<RawGamepadEvent>,
-    mut connection_events: MessageWriter<GamepadConnectionEvent>,
-    mut button_events: MessageWriter<RawGamepadButtonChangedEvent>,
-    mut axis_event: MessageWriter<RawGamepadAxisChangedEvent>,
+    mut gamepads: Query<(HidDeviceState, Has<Gamepad>), With<HidDevice>>,
+    mut writers: GamepadWriters,
     assets: Res<Assets<HidAsset>>,
+    time: Res<Time>,
 ) {
     let api = hid.cell.get();
 
@@ -75,43 +257,42 @@ pub(crate) fn update_hid_devices(
 
         let id = DeviceId::from(device);
 
-        let Some(handle) = resources.assets.get(&id) else {
+        let Some(handle) = resources.asset_for(&id).cloned() else {
             continue;
         };
 
-        let Some(asset) = assets.get(handle) else {
+        let Some(asset) = assets.get(&handle) else {
             continue;
         };
 
-        if !resources.connected.contains_key(&path) {
-            // New device- initiate it.
-            let gamepad = commands.spawn(HidBuffer::default()).id();
-
-            let event = GamepadConnectionEvent::new(
-                gamepad,
-                GamepadConnection::Connected {
-                    name: asset.name().to_string(),
-                    vendor_id: Some(device.vendor_id()),
-                    product_id: Some(device.product_id()),
-                },
-            );
-            events.write(event.clone().into());
-            connection_events.write(event);
-            resources.connected.insert(path, gamepad);
-
-            continue;
-        }
-
-        let Some(gamepad) = resources.connected.get(&path) else {
-            continue;
+        let gamepad = match resources.connected.get(&path) {
+            Some(gamepad) => *gamepad,
+            None => {
+                let gamepad = commands.spawn_empty().id();
+                resources.connected.insert(path, gamepad);
+                gamepad
+            }
         };
-        let gamepad = *gamepad;
 
-        let Ok(mut buffer_component) = gamepads.get_mut(gamepad) else {
+        let Ok((mut state, has_gamepad)) = gamepads.get_mut(gamepad) else {
+            // New device, or one whose mapping was unregistered and has one again- initiate it.
+            commands.entity(gamepad).try_insert((
+                HidDevice { id, asset: handle },
+                HidBuffer::default(),
+                ButtonAxisState::default(),
+                ChordState::default(),
+                PulseState::default(),
+                HidSwitches::default(),
+                HidAxisZones::default(),
+            ));
+            writers.connected(gamepad, asset, id);
             continue;
         };
+        if !has_gamepad {
+            continue;
+        }
 
-        let Ok(stream) = device.open_device(&api) else {
+        let Ok(stream) = device.open_device(api) else {
             continue;
         };
 
@@ -122,83 +303,198 @@ pub(crate) fn update_hid_devices(
         // Get last known state or default to zeros
         let mut buf = [0u8; 256];
 
-        let buf_last = buffer_component.0.clone();
-        let buf_new = match stream.read(&mut buf[..]) {
-            Ok(n) if n > 0 => &mut buf,
-            _ => {
-                continue;
+        let last = *state.buffer;
+        if let Ok(n) = stream.read(&mut buf[..])
+            && n > 0
+        {
+            *state.buffer = HidBuffer(buf, true);
+            let now = time.elapsed_secs();
+            process_report(gamepad, asset, &buf, &last, &mut state, now, &mut writers);
+        }
+
+        // Pulses queued by relative axes are sent one step per frame.
+        for button in state.pulses.next() {
+            writers.pulse(gamepad, button);
+        }
+
+        // Button axes can ramp while held, so these are stepped every frame regardless of new reports.
+        for (axis, ptr) in &asset.input_mapping.button_axes {
+            let current = state.button_axes.get(*axis);
+            let value = ptr.step(current, &state.buffer.0, time.delta_secs());
+            if value != current {
+                state.button_axes.0.insert(*axis, value);
+                writers.axis(gamepad, *axis, value);
             }
+        }
+    }
+}
+
+/// Compares a freshly read report against the last one and writes events for anything that changed.
+fn process_report(
+    gamepad: Entity,
+    asset: &HidAsset,
+    buf_new: &[u8],
+    last: &HidBuffer,
+    state: &mut HidDeviceStateItem,
+    now: f32,
+    writers: &mut GamepadWriters,
+) {
+    let calibration = state.calibration;
+    let HidBuffer(buf_last, seeded) = last;
+    let buf_last = &buf_last[..];
+
+    // 1. Process Buttons using BufferMap for efficiency. A layer change can move any button, so all are checked then.
+    let map = &asset.input_mapping;
+    let layer_new = map.active_layer(buf_new);
+    let layer_last = map.active_layer(buf_last);
+    let changed = if layer_new == layer_last {
+        asset.buffer_map.changed(buf_new, buf_last)
+    } else {
+        map.buttons.keys().copied().collect()
+    };
+    // Chords are resolved first so that any buttons they mute are never written.
+    let chord_events = state.chords.update(map, &changed, buf_new, buf_last, now);
+    for button in changed {
+        // We know these are buttons because BufferMap only stores buttons now
+        let Some(ptr) = map.buttons.get(&button) else {
+            continue;
         };
-        buffer_component.0 = *buf_new;
+        if state.chords.suppress(button, ptr.pressed(buf_new)) {
+            continue;
+        }
+        let (value, value_last) = (ptr.value(buf_new), ptr.value(buf_last));
+        let (target, target_last) = (
+            map.resolve(button, layer_new),
+            map.resolve(button, layer_last),
+        );
+        if target == target_last {
+            if value != value_last {
+                writers.button(gamepad, target, value);
+            }
+            continue;
+        }
+        // Release whatever the button emitted under the old layer before pressing the new one.
+        if value_last != 0.0 {
+            writers.button(gamepad, target_last, 0.0);
+        }
+        if value != 0.0 {
+            writers.button(gamepad, target, value);
+        }
+    }
+    for (button, value) in chord_events {
+        writers.button(gamepad, button, value);
+    }
 
-        // 1. Process Buttons using BufferMap for efficiency
-        for (i, buttons) in asset.buffer_map.iter() {
-            let i = *i as usize;
+    // 2. Process Axes by iterating them directly. Values are collected so zones can be checked once sticks are done too.
+    // On the first report every axis counts as changed, so the zones an axis rests in are entered straight away.
+    let previous = seeded.then_some(buf_last);
+    let mut axis_values = Vec::new();
+    for (axis, ptr) in &asset.input_mapping.axes {
+        if asset.input_mapping.stick(*axis).is_some() {
+            continue;
+        }
+        let calibration = calibration.and_then(|c| c.get(*axis));
+        if let Some(value) = proccess_axis(ptr, buf_new, previous, calibration) {
+            axis_values.push((*axis, value));
+        }
+    }
 
-            let last = buf_last[i];
-            let new = buf_new[i];
+    // 3. Process relative axes, which only move when a report comes in. Counters have nothing to diff against
+    // until a report has been read, so that first report only seeds them.
+    for (axis, ptr) in &asset.input_mapping.relative_axes {
+        if !*seeded && ptr.encoding == RelativeEncoding::Counter {
+            continue;
+        }
+        let delta = ptr.delta(buf_new, buf_last);
+        if delta == 0 {
+            continue;
+        }
+        writers.relative(gamepad, *axis, delta);
+        let pulse = if delta > 0 {
+            ptr.increment
+        } else {
+            ptr.decrement
+        };
+        if let Some(button) = pulse {
+            state.pulses.queue(button, delta.unsigned_abs());
+        }
+    }
 
-            if last == new {
-                continue;
-            }
+    // 4. Process switches, which hold their current position on the gamepad
+    for (name, ptr) in &asset.input_mapping.switches {
+        let position = ptr.position(buf_new);
+        if state.switches.get(name) == position {
+            continue;
+        }
+        let position = position.map(str::to_string);
+        let previous = state.switches.set(name, position.clone());
+        writers.switch(gamepad, name, position, previous);
+    }
 
-            for button in buttons.iter() {
-                // We know these are buttons because BufferMap only stores buttons now
-                if let Some(ptr) = asset.input_mapping.buttons.get(button) {
-                    let pressed = match ptr.1 {
-                        ButtonQuery::Bit(mask) => (new & mask) != 0,
-                        ButtonQuery::Eq(val) => new == val,
-                    };
-                    let was_pressed = match ptr.1 {
-                        ButtonQuery::Bit(mask) => (last & mask) != 0,
-                        ButtonQuery::Eq(val) => last == val,
-                    };
-                    if pressed != was_pressed {
-                        let event = RawGamepadButtonChangedEvent::new(
-                            gamepad,
-                            *button,
-                            if pressed { 1.0 } else { 0.0 },
-                        );
-                        events.write(event.clone().into());
-                        button_events.write(event);
-                    }
-                }
-            }
+    // 5. Process sticks, which need both axes to apply a radial deadzone. Half-bound sticks were read as plain axes.
+    for stick in &asset.input_mapping.sticks {
+        if let Some((x, y)) = proccess_stick(stick, asset, buf_new, previous, calibration) {
+            axis_values.push((stick.x, x));
+            axis_values.push((stick.y, y));
         }
+    }
 
-        // 2. Process Axes by iterating them directly
-        for (axis, ptr) in &asset.input_mapping.axes {
-            if let Some(value) = proccess_axis(ptr, buf_new, &buf_last) {
-                let event = RawGamepadAxisChangedEvent::new(gamepad, *axis, value);
-                events.write(event.clone().into());
-                axis_event.write(event);
+    // 6. Write the axis values and track which zones they sit in
+    for (axis, value) in axis_values {
+        writers.axis(gamepad, axis, value);
+        let Some(ptr) = asset.input_mapping.axes.get(&axis) else {
+            continue;
+        };
+        for zone in &ptr.zones {
+            let inside = zone.contains(value);
+            if inside != state.zones.contains(axis, &zone.name) {
+                state.zones.set(axis, &zone.name, inside);
+                let transition = if inside {
+                    ZoneTransition::Entered
+                } else {
+                    ZoneTransition::Left
+                };
+                writers.zone(gamepad, axis, &zone.name, transition);
             }
         }
     }
 }
 
-fn proccess_axis(ptr: &AxisPointer, buf_new: &[u8], buf_last: &[u8]) -> Option<f32> {
-    let fine_val = buf_new[ptr.fine as usize];
-    let coarse_val = buf_new[ptr.coarse as usize];
-
-    let fine_last = buf_last[ptr.fine as usize];
-    let coarse_last = buf_last[ptr.coarse as usize];
-
-    if fine_val == fine_last && coarse_val == coarse_last {
+fn proccess_stick(
+    stick: &StickPair,
+    asset: &HidAsset,
+    buf_new: &[u8],
+    buf_last: Option<&[u8]>,
+    calibration: Option<&HidCalibration>,
+) -> Option<(f32, f32)> {
+    let axes = &asset.input_mapping.axes;
+    let (x_ptr, y_ptr) = (axes.get(&stick.x)?, axes.get(&stick.y)?);
+    if let Some(buf_last) = buf_last
+        && !x_ptr.changed(buf_new, buf_last)
+        && !y_ptr.changed(buf_new, buf_last)
+    {
         return None;
     }
+    let x = x_ptr.normalize(buf_new, calibration.and_then(|c| c.get(stick.x)));
+    let y = y_ptr.normalize(buf_new, calibration.and_then(|c| c.get(stick.y)));
+    let (x, y) = stick.apply(x, y);
+    Some((
+        x_ptr.curve.apply(x.abs()).copysign(x),
+        y_ptr.curve.apply(y.abs()).copysign(y),
+    ))
+}
 
-    let max_raw = 255 * ptr.octaves as u32;
-    let mut raw_value = (coarse_val as u32 * 255) + fine_val as u32;
-    if ptr.inverted {
-        raw_value = max_raw - raw_value;
+/// Reads an axis if it changed since `buf_last`. Without a last report, the axis is always read.
+fn proccess_axis(
+    ptr: &AxisPointer,
+    buf_new: &[u8],
+    buf_last: Option<&[u8]>,
+    calibration: Option<&AxisCalibration>,
+) -> Option<f32> {
+    if buf_last.is_some_and(|buf_last| !ptr.changed(buf_new, buf_last)) {
+        return None;
     }
-    let normalized = if !ptr.abs {
-        ((raw_value as f32 / (max_raw as f32 * 0.5)) - 1.0).clamp(-1.0, 1.0)
-    } else {
-        (raw_value as f32 / max_raw as f32).clamp(0.0, 1.0)
-    };
-    Some(normalized)
+    Some(ptr.value(buf_new, calibration))
 }
 
 // test
@@ -214,10 +510,134 @@ mod tests {
             octaves: 256,
             inverted: false,
             abs: false,
+            ..Default::default()
         };
         let buf_new = [255, 255];
         let buf_last = [254, 255];
-        let value = proccess_axis(&ptr, &buf_new, &buf_last).unwrap();
+        let value = proccess_axis(&ptr, &buf_new, Some(&buf_last), None).unwrap();
         assert_eq!(value, 1.0);
     }
+
+    #[test]
+    fn test_proccess_axis_calibrated() {
+        let ptr = AxisPointer {
+            fine: 0,
+            coarse: 1,
+            octaves: 4,
+            inverted: false,
+            abs: false,
+            calibration: Some(AxisCalibration {
+                min: 100,
+                center: Some(400),
+                max: 900,
+            }),
+            ..Default::default()
+        };
+        let buf_last = [0, 0];
+        assert_eq!(
+            proccess_axis(&ptr, &[145, 1], Some(&buf_last), None),
+            Some(0.0)
+        );
+        assert_eq!(
+            proccess_axis(&ptr, &[50, 0], Some(&buf_last), None),
+            Some(-1.0)
+        );
+        assert_eq!(
+            proccess_axis(&ptr, &[0, 4], Some(&buf_last), None),
+            Some(1.0)
+        );
+
+        let worn = AxisCalibration {
+            min: 0,
+            center: None,
+            max: 510,
+        };
+        assert_eq!(
+            proccess_axis(&ptr, &[0, 2], Some(&buf_last), Some(&worn)),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn test_half_bound_stick() {
+        let mut map = crate::device::DeviceMap::default();
+        map.axes
+            .insert(GamepadAxis::LeftStickX, AxisPointer::default());
+        map.sticks.push(StickPair {
+            x: GamepadAxis::LeftStickX,
+            y: GamepadAxis::LeftStickY,
+            deadzone: Some(0.25),
+            saturation: None,
+        });
+        assert!(map.stick(GamepadAxis::LeftStickX).is_none());
+
+        map.axes
+            .insert(GamepadAxis::LeftStickY, AxisPointer::default());
+        assert!(map.stick(GamepadAxis::LeftStickX).is_some());
+    }
+
+    #[test]
+    fn test_first_report_zone() {
+        use crate::{buffers::PulseState, input::AxisZone};
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut map = crate::device::DeviceMap::default();
+        let idle = AxisZone {
+            name: "idle".to_string(),
+            min: 0.0,
+            max: 0.05,
+        };
+        map.axes.insert(
+            GamepadAxis::RightZ,
+            AxisPointer {
+                fine: 1,
+                coarse: 2,
+                octaves: 256,
+                abs: true,
+                zones: vec![idle],
+                ..Default::default()
+            },
+        );
+        let asset = HidAsset::from(&DeviceAsset::new("Throttle".to_string(), 1, 2, map));
+
+        let mut app = App::new();
+        app.add_message::<RawGamepadEvent>()
+            .add_message::<GamepadConnectionEvent>()
+            .add_message::<RawGamepadButtonChangedEvent>()
+            .add_message::<RawGamepadAxisChangedEvent>()
+            .add_message::<HidRelativeAxisMoved>()
+            .add_message::<HidSwitchChanged>()
+            .add_message::<HidAxisZoneChanged>();
+        let gamepad = app
+            .world_mut()
+            .spawn((
+                HidBuffer::default(),
+                ButtonAxisState::default(),
+                ChordState::default(),
+                PulseState::default(),
+                HidSwitches::default(),
+                HidAxisZones::default(),
+            ))
+            .id();
+
+        // The throttle rests at raw 0, which matches the zeroed buffer it starts with.
+        app.world_mut()
+            .run_system_once(
+                move |mut states: Query<HidDeviceState>, mut writers: GamepadWriters| {
+                    let mut state = states.get_mut(gamepad).unwrap();
+                    let last = *state.buffer;
+                    let buf = [0; 256];
+                    process_report(gamepad, &asset, &buf, &last, &mut state, 0.0, &mut writers);
+                },
+            )
+            .unwrap();
+        let zones = app.world().get::<HidAxisZones>(gamepad).unwrap();
+        assert!(zones.contains(GamepadAxis::RightZ, "idle"));
+        let entered = app
+            .world_mut()
+            .resource_mut::<Messages<HidAxisZoneChanged>>()
+            .drain()
+            .count();
+        assert_eq!(entered, 1);
+    }
 }
@@ -1,6 +1,12 @@
-use crate::device::DeviceAsset;
-use bevy::prelude::*;
-use std::fs::read_dir;
+use crate::{
+    assets::{HidAssetLoadError, HidAssetSaveError},
+    device::DeviceAsset,
+    migration::migrate_path,
+};
+use std::{
+    fs::read_dir,
+    path::{Path, PathBuf},
+};
 
 /// Loads devices from disk.
 pub fn load_raw_devices() -> Vec<DeviceAsset> {
@@ -13,8 +19,7 @@ pub fn load_raw_devices() -> Vec<DeviceAsset> {
         let device = device.expect("failed to read device");
         let path = device.path();
 
-        let content = std::fs::read_to_string(path).expect("failed to read device file");
-        let hid_device: DeviceAsset = match ron::from_str(&content) {
+        let hid_device = match load_device(path) {
             Ok(device) => device,
             Err(e) => {
                 println!("failed to deserialize device: {}", e);
@@ -27,14 +32,130 @@ pub fn load_raw_devices() -> Vec<DeviceAsset> {
     all_devices
 }
 
+/// Loads a single device from disk, resolving any base assets it inherits from.
+pub fn load_device(path: impl AsRef<Path>) -> Result<DeviceAsset, HidAssetLoadError> {
+    load_device_chain(path.as_ref(), &mut Vec::new())
+}
+
+fn load_device_chain(
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<DeviceAsset, HidAssetLoadError> {
+    let canonical = path.canonicalize()?;
+    if chain.contains(&canonical) {
+        return Err(HidAssetLoadError::BaseCycle(path.display().to_string()));
+    }
+    chain.push(canonical);
+
+    let bytes = std::fs::read(path)?;
+    let (mut device, _) = migrate_path(path, &bytes)?;
+    if let Some(base) = device.base() {
+        let base_path = path.parent().unwrap_or(Path::new("")).join(base);
+        let base = load_device_chain(&base_path, chain)?;
+        device.mappings_mut().inherit(base.mappings());
+    }
+    Ok(device)
+}
+
+/// Saves a device to disk as a `.hid.ron` file.
+pub fn save_device(device: &DeviceAsset, path: impl AsRef<Path>) -> Result<(), HidAssetSaveError> {
+    let content = device.to_ron()?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+/// A fresh directory per test run, so parallel runs never share files.
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("bevy_hid_{name}_{}_{nanos}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
 // test modules
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::assets::ron_options;
 
     #[test]
     fn test_load_raw_devices() {
         let devices = load_raw_devices();
         assert!(!devices.is_empty());
+        for device in devices {
+            let report = device.mappings().validate();
+            assert!(report.is_valid(), "{}: {:?}", device.name(), report.errors);
+        }
+    }
+
+    #[test]
+    fn test_device_labels() {
+        use crate::bindings::Binding;
+        use bevy::prelude::GamepadButton;
+
+        let throttle = load_raw_devices()
+            .into_iter()
+            .find(|device| device.name() == "TWCS Throttle (HID)")
+            .unwrap();
+        let label = throttle
+            .mappings()
+            .label_for(Binding::Button(GamepadButton::Other(2)))
+            .unwrap();
+        assert_eq!(label.short_or_label(), "Pinky");
+        assert_eq!(label.short_in(Some("de-AT")), "Klein");
+        assert_eq!(label.label_in(Some("fr")), "Pinky button");
+    }
+
+    #[test]
+    fn test_device_round_trip() {
+        for device in load_raw_devices() {
+            let content = device.to_ron().unwrap();
+            let reloaded: DeviceAsset = ron_options().from_str(&content).unwrap();
+            assert_eq!(reloaded.mappings(), device.mappings());
+            assert_eq!(reloaded.to_ron().unwrap(), content);
+        }
+    }
+
+    #[test]
+    fn test_load_device_with_base() {
+        let dir = temp_dir("inheritance");
+        std::fs::write(
+            dir.join("base.hid.ron"),
+            r#"(version: 2, name: "Base", ids: [], mappings: (
+                buttons: { South: (1, Bit(1)), East: (1, Bit(2)) },
+                axes: {},
+            ))"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("child.hid.ron"),
+            r#"(version: 2, name: "Child", ids: [Id(vid: 1, pid: 2)], base: "base.hid.ron", mappings: (
+                buttons: { East: (2, Bit(2)) },
+                axes: {},
+            ))"#,
+        )
+        .unwrap();
+
+        let device = load_device(dir.join("child.hid.ron")).unwrap();
+        let buttons = &device.mappings().buttons;
+        assert_eq!(buttons.len(), 2);
+        assert_eq!(buttons[&bevy::prelude::GamepadButton::East].0, 2);
+        assert_eq!(buttons[&bevy::prelude::GamepadButton::South].0, 1);
+
+        std::fs::write(
+            dir.join("loop.hid.ron"),
+            r#"(version: 2, name: "Loop", ids: [], base: "loop.hid.ron", mappings: (buttons: {}, axes: {}))"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            load_device(dir.join("loop.hid.ron")),
+            Err(HidAssetLoadError::BaseCycle(_))
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,595 @@
+use std::fmt::{self, Debug, Display};
+
+use bevy::prelude::{GamepadAxis, GamepadButton};
+use thiserror::Error;
+
+use crate::{
+    bindings::Binding,
+    device::{DeviceAsset, DeviceMap, DeviceMatch},
+    input::{AxisPointer, ButtonPointer, ButtonQuery, ResponseCurve},
+};
+
+/// The size of the buffer reports are read into.
+pub const REPORT_SIZE: usize = 256;
+
+/// A problem that stops a binding from working.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    #[error("reads byte {index}, beyond the end of the {size} byte report")]
+    OutOfRange { index: usize, size: usize },
+    #[error("has an empty bit mask and can never be pressed")]
+    EmptyMask,
+    #[error(
+        "compares against {value:#04x}, which has bits outside the mask {mask:#04x} and can never match"
+    )]
+    UnmatchableMask { mask: u8, value: u8 },
+    #[error("has a range from {min} to {max}, which is empty")]
+    EmptyRange { min: u8, max: u8 },
+    #[error("has no octaves")]
+    ZeroOctaves,
+    #[error("reads its fine and coarse values from the same byte")]
+    SharedAxisByte,
+    #[error("is calibrated with a max of {max}, which is not above its min of {min}")]
+    InvalidCalibration { min: u32, max: u32 },
+    #[error("has a zone {0:?} whose min is above its max")]
+    InvalidZone(String),
+    #[error("pairs {0:?}, which is not bound as an axis")]
+    MissingStickAxis(GamepadAxis),
+    #[error("has no buttons and would always be held")]
+    EmptyChord,
+    #[error("has a product id range from {min} to {max}, which matches no device")]
+    InvertedRange { min: u16, max: u16 },
+    #[error("is also bound in {0}, and the two would overwrite each other")]
+    AlsoBound(&'static str),
+}
+
+/// Something that is likely to be a mistake, but still loads.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ValidationWarning {
+    #[error("has a deadzone of {deadzone}, which is not below its saturation of {saturation}")]
+    DeadzoneBeyondSaturation { deadzone: f32, saturation: f32 },
+    #[error("is calibrated up to {max}, beyond the largest raw value of {max_raw}")]
+    CalibrationBeyondRange { max: u32, max_raw: u32 },
+    #[error("reads byte {index}, which {other:?} also reads")]
+    SharedByte { index: u8, other: GamepadAxis },
+    #[error("has spline points that are not sorted by input")]
+    UnsortedSpline,
+    #[error("refers to {0:?}, which is not bound")]
+    Unbound(Binding),
+    #[error("has no positions")]
+    EmptySwitch,
+}
+
+/// A validation error or warning, and the binding it was found on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue<T> {
+    pub issue: T,
+    /// The section of the mapping the binding is in, e.g. `axes`.
+    pub section: &'static str,
+    /// The binding, as it is written in the asset (e.g. `Other(1)`).
+    pub key: String,
+    /// Where the binding was found in the asset source, if it was given.
+    pub span: Option<Span>,
+}
+
+/// A line and column (both from 1) in an asset source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Everything found while validating a [`DeviceMap`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    pub errors: Vec<ValidationIssue<ValidationError>>,
+    pub warnings: Vec<ValidationIssue<ValidationWarning>>,
+}
+
+impl<T: Display> Display for ValidationIssue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.section, self.key)?;
+        if let Some(span) = self.span {
+            write!(f, " (line {}, column {})", span.line, span.column)?;
+        }
+        write!(f, " {}", self.issue)
+    }
+}
+
+impl ValidationReport {
+    /// True if there are no errors. There may still be warnings.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Finds where each issue's binding is in the asset source. This is a text search, so it is best-effort:
+    /// issues that can't be found keep no span.
+    pub fn with_spans(mut self, source: &str) -> Self {
+        for error in &mut self.errors {
+            error.span = locate(source, error.section, &error.key);
+        }
+        for warning in &mut self.warnings {
+            warning.span = locate(source, warning.section, &warning.key);
+        }
+        self
+    }
+}
+
+impl DeviceMap {
+    /// Checks the bindings for mistakes, assuming reports are read into a [`REPORT_SIZE`] buffer.
+    pub fn validate(&self) -> ValidationReport {
+        self.validate_report_size(REPORT_SIZE)
+    }
+
+    /// Checks the bindings for mistakes against a known report size.
+    pub fn validate_report_size(&self, size: usize) -> ValidationReport {
+        let mut validator = Validator {
+            map: self,
+            size,
+            report: ValidationReport::default(),
+        };
+        validator.run();
+        validator.report
+    }
+}
+
+impl DeviceAsset {
+    /// Checks the device ids and the bindings for mistakes against a known report size.
+    pub fn validate_report_size(&self, size: usize) -> ValidationReport {
+        let mut report = self.mappings.validate_report_size(size);
+        let ids = self.ids.iter().enumerate().filter_map(|(i, id)| match *id {
+            DeviceMatch::Range { min, max, .. } if min > max => Some(ValidationIssue {
+                issue: ValidationError::InvertedRange { min, max },
+                section: "ids",
+                key: i.to_string(),
+                span: None,
+            }),
+            _ => None,
+        });
+        report.errors.splice(0..0, ids);
+        report
+    }
+}
+
+struct Validator<'a> {
+    map: &'a DeviceMap,
+    size: usize,
+    report: ValidationReport,
+}
+
+impl Validator<'_> {
+    fn run(&mut self) {
+        let map = self.map;
+        for (button, ptr) in &map.buttons {
+            self.button("buttons", key(button), ptr);
+        }
+        for (axis, ptr) in &map.axes {
+            self.axis(*axis, ptr);
+        }
+        for (axis, ptr) in &map.button_axes {
+            if map.axes.contains_key(axis) {
+                self.error("button_axes", key(axis), ValidationError::AlsoBound("axes"));
+            }
+            self.button("button_axes", key(axis), &ptr.negative);
+            self.button("button_axes", key(axis), &ptr.positive);
+        }
+        for (axis, ptr) in &map.relative_axes {
+            let other = if map.axes.contains_key(axis) {
+                Some("axes")
+            } else if map.button_axes.contains_key(axis) {
+                Some("button_axes")
+            } else {
+                None
+            };
+            if let Some(other) = other {
+                self.error(
+                    "relative_axes",
+                    key(axis),
+                    ValidationError::AlsoBound(other),
+                );
+            }
+            self.range("relative_axes", key(axis), ptr.index, ptr.width());
+            for button in [ptr.increment, ptr.decrement].into_iter().flatten() {
+                self.bound("relative_axes", key(axis), button);
+            }
+        }
+        for (name, switch) in &map.switches {
+            if switch.positions.is_empty() {
+                self.warn("switches", name.clone(), ValidationWarning::EmptySwitch);
+            }
+            for (_, ptr) in &switch.positions {
+                self.button("switches", name.clone(), ptr);
+            }
+        }
+        for (i, stick) in map.sticks.iter().enumerate() {
+            for axis in [stick.x, stick.y] {
+                if !map.axes.contains_key(&axis) {
+                    let error = ValidationError::MissingStickAxis(axis);
+                    self.error("sticks", format!("{i}"), error);
+                }
+            }
+        }
+        for layer in &map.layers {
+            self.button("layers", layer.name.clone(), &layer.modifier);
+            for button in layer.buttons.keys() {
+                self.bound("layers", layer.name.clone(), *button);
+            }
+        }
+        for (chord, ptr) in &map.chords {
+            if ptr.buttons.is_empty() {
+                self.error("chords", key(chord), ValidationError::EmptyChord);
+            }
+            if map.buttons.contains_key(chord) {
+                self.error("chords", key(chord), ValidationError::AlsoBound("buttons"));
+            }
+            for button in &ptr.buttons {
+                self.bound("chords", key(chord), *button);
+            }
+        }
+        for binding in map.labels.keys() {
+            if !is_bound(map, binding) {
+                let warning = ValidationWarning::Unbound(*binding);
+                self.warn("labels", key(binding), warning);
+            }
+        }
+
+        // Hash map order is arbitrary, so sort to keep reports stable.
+        let order = |section: &str| {
+            [
+                "buttons",
+                "axes",
+                "button_axes",
+                "relative_axes",
+                "switches",
+                "sticks",
+                "layers",
+                "chords",
+                "labels",
+            ]
+            .iter()
+            .position(|s| *s == section)
+        };
+        self.report
+            .errors
+            .sort_by(|a, b| (order(a.section), &a.key).cmp(&(order(b.section), &b.key)));
+        self.report
+            .warnings
+            .sort_by(|a, b| (order(a.section), &a.key).cmp(&(order(b.section), &b.key)));
+    }
+
+    fn button(&mut self, section: &'static str, key: String, ptr: &ButtonPointer) {
+        self.range(section, key.clone(), ptr.0, ptr.1.width());
+        if let ButtonQuery::BitPressure(_, pressure) = ptr.1 {
+            self.range(section, key.clone(), pressure.index, 1);
+        }
+        match ptr.1 {
+            ButtonQuery::Bit(0)
+            | ButtonQuery::Bit16(0)
+            | ButtonQuery::Bit32(0)
+            | ButtonQuery::BitPressure(0, _) => {
+                self.error(section, key, ValidationError::EmptyMask);
+            }
+            ButtonQuery::Masked(mask, value) if value & !mask != 0 => {
+                let error = ValidationError::UnmatchableMask { mask, value };
+                self.error(section, key, error);
+            }
+            ButtonQuery::Range(min, max) if min > max => {
+                self.error(section, key, ValidationError::EmptyRange { min, max });
+            }
+            _ => {}
+        }
+    }
+
+    fn axis(&mut self, axis: GamepadAxis, ptr: &AxisPointer) {
+        let section = "axes";
+        self.range(section, key(&axis), ptr.fine, 1);
+        self.range(section, key(&axis), ptr.coarse, 1);
+        if ptr.octaves == 0 {
+            self.error(section, key(&axis), ValidationError::ZeroOctaves);
+        }
+        if ptr.fine == ptr.coarse {
+            self.error(section, key(&axis), ValidationError::SharedAxisByte);
+        }
+        if let Some(calibration) = ptr.calibration {
+            if calibration.max <= calibration.min {
+                let error = ValidationError::InvalidCalibration {
+                    min: calibration.min,
+                    max: calibration.max,
+                };
+                self.error(section, key(&axis), error);
+            } else if calibration.max > ptr.max_raw() {
+                let warning = ValidationWarning::CalibrationBeyondRange {
+                    max: calibration.max,
+                    max_raw: ptr.max_raw(),
+                };
+                self.warn(section, key(&axis), warning);
+            }
+        }
+        if let (Some(deadzone), Some(saturation)) = (ptr.deadzone, ptr.saturation)
+            && deadzone >= saturation
+        {
+            let warning = ValidationWarning::DeadzoneBeyondSaturation {
+                deadzone,
+                saturation,
+            };
+            self.warn(section, key(&axis), warning);
+        }
+        if let ResponseCurve::Spline(points) = &ptr.curve
+            && points.windows(2).any(|pair| pair[0].0 > pair[1].0)
+        {
+            self.warn(section, key(&axis), ValidationWarning::UnsortedSpline);
+        }
+        for zone in &ptr.zones {
+            if zone.min > zone.max {
+                let error = ValidationError::InvalidZone(zone.name.clone());
+                self.error(section, key(&axis), error);
+            }
+        }
+        for (other, other_ptr) in &self.map.axes {
+            if *other == axis {
+                continue;
+            }
+            for index in [ptr.fine, ptr.coarse] {
+                if index == other_ptr.fine || index == other_ptr.coarse {
+                    let warning = ValidationWarning::SharedByte {
+                        index,
+                        other: *other,
+                    };
+                    self.warn(section, key(&axis), warning);
+                }
+            }
+        }
+    }
+
+    fn range(&mut self, section: &'static str, key: String, index: u8, width: u8) {
+        let last = index as usize + width.max(1) as usize - 1;
+        if last >= self.size {
+            let error = ValidationError::OutOfRange {
+                index: last,
+                size: self.size,
+            };
+            self.error(section, key, error);
+        }
+    }
+
+    fn bound(&mut self, section: &'static str, key: String, button: GamepadButton) {
+        if !self.map.buttons.contains_key(&button) {
+            self.warn(section, key, ValidationWarning::Unbound(button.into()));
+        }
+    }
+
+    fn error(&mut self, section: &'static str, key: String, issue: ValidationError) {
+        self.report.errors.push(ValidationIssue {
+            issue,
+            section,
+            key,
+            span: None,
+        });
+    }
+
+    fn warn(&mut self, section: &'static str, key: String, issue: ValidationWarning) {
+        self.report.warnings.push(ValidationIssue {
+            issue,
+            section,
+            key,
+            span: None,
+        });
+    }
+}
+
+/// Checks if anything emits the binding, including virtual buttons and axes.
+fn is_bound(map: &DeviceMap, binding: &Binding) -> bool {
+    match binding {
+        Binding::Button(button) => {
+            map.buttons.contains_key(button)
+                || map.chords.contains_key(button)
+                || map
+                    .layers
+                    .iter()
+                    .any(|layer| layer.buttons.values().any(|b| b == button))
+                || map
+                    .relative_axes
+                    .values()
+                    .any(|ptr| ptr.increment == Some(*button) || ptr.decrement == Some(*button))
+        }
+        Binding::Axis(axis) => {
+            map.axes.contains_key(axis)
+                || map.button_axes.contains_key(axis)
+                || map.relative_axes.contains_key(axis)
+        }
+    }
+}
+
+fn key(value: &impl Debug) -> String {
+    format!("{value:?}")
+}
+
+/// A piece of an asset source that `locate` cares about. Everything else, including comments, is skipped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    /// A run of identifier characters, or a number.
+    Word,
+    /// The contents of a quoted string.
+    Str(&'a str),
+    Open,
+    Close,
+    /// `:` or `=`, between a field and its value.
+    Assign,
+}
+
+/// Splits an asset source into tokens, with the byte offset each starts at and how deeply it is nested.
+/// This understands just enough of RON, JSON and TOML to skip strings and comments.
+fn tokenize(source: &str) -> Vec<(usize, usize, Token<'_>)> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    let mut chars = source.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '/' if chars.peek().is_some_and(|(_, c)| *c == '/') => {
+                chars.find(|(_, c)| *c == '\n');
+            }
+            '#' => {
+                chars.find(|(_, c)| *c == '\n');
+            }
+            '/' if chars.peek().is_some_and(|(_, c)| *c == '*') => {
+                chars.next();
+                let mut last = ' ';
+                for (_, c) in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '"' | '\'' => {
+                let mut end = source.len();
+                let mut escaped = false;
+                for (i, next) in chars.by_ref() {
+                    if next == c && !escaped {
+                        end = i;
+                        break;
+                    }
+                    escaped = next == '\\' && !escaped;
+                }
+                tokens.push((start, depth, Token::Str(&source[start + 1..end])));
+            }
+            '(' | '[' | '{' => {
+                tokens.push((start, depth, Token::Open));
+                depth += 1;
+            }
+            ')' | ']' | '}' => {
+                depth = depth.saturating_sub(1);
+                tokens.push((start, depth, Token::Close));
+            }
+            ':' | '=' => tokens.push((start, depth, Token::Assign)),
+            c if is_word(c) => {
+                while chars.next_if(|(_, c)| is_word(*c)).is_some() {}
+                tokens.push((start, depth, Token::Word));
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Checks if the token at `start` is `text`, either as whole words (so `Other(1)` matches, but `axes` does not match
+/// `button_axes`) or as the entire contents of a string.
+fn token_is(source: &str, start: usize, token: Token, text: &str) -> bool {
+    match token {
+        Token::Word => {
+            source[start..].starts_with(text)
+                && !source[start + text.len()..]
+                    .starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        }
+        Token::Str(contents) => contents == text,
+        _ => false,
+    }
+}
+
+/// Finds `key` inside the `section` field nearest the top level of the source, falling back to the section itself
+/// if the key can't be found. Keys match whole words, or strings holding exactly the key (as JSON writes them).
+fn locate(source: &str, section: &str, key: &str) -> Option<Span> {
+    let tokens = tokenize(source);
+    let section_index = (0..tokens.len())
+        .filter(|i| {
+            let (start, _, token) = tokens[*i];
+            token_is(source, start, token, section)
+                && matches!(tokens.get(i + 1), Some((_, _, Token::Assign)))
+        })
+        .min_by_key(|i| tokens[*i].1)?;
+    let (section_start, depth, _) = tokens[section_index];
+
+    // The section's value is the bracketed map or list after it. Keys sit directly inside it, or inside its
+    // entries when a map is written as a list of `[key, value]` pairs.
+    let mut offset = section_start;
+    let mut entered = false;
+    for &(start, token_depth, token) in &tokens[section_index + 2..] {
+        match token {
+            Token::Open if token_depth == depth => entered = true,
+            Token::Close if token_depth == depth => break,
+            _ if entered && token_depth <= depth + 2 && token_is(source, start, token, key) => {
+                offset = start;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+    Some(Span { line, column })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::ron_options;
+
+    #[test]
+    fn test_validate_spans() {
+        let source = "(
+    // the axes: LeftStickX binding below is broken
+    buttons: {
+        South: (3, Bit(1)),
+        Other(1): (3, Bit(0)),
+    },
+    button_axes: {},
+    axes: {
+        LeftStickX: (fine: 1, coarse: 1, octaves: 0, inverted: false, abs: false),
+    },
+)";
+        let map: DeviceMap = ron_options().from_str(source).unwrap();
+        let report = map.validate().with_spans(source);
+        let errors: Vec<_> = report.errors.iter().map(|e| &e.issue).collect();
+        assert_eq!(
+            errors,
+            [
+                &ValidationError::EmptyMask,
+                &ValidationError::ZeroOctaves,
+                &ValidationError::SharedAxisByte
+            ]
+        );
+        assert_eq!(report.errors[0].span, Some(Span { line: 5, column: 9 }));
+        assert_eq!(
+            report.errors[1].to_string(),
+            "axes.LeftStickX (line 9, column 9) has no octaves"
+        );
+    }
+
+    #[test]
+    fn test_validate_report_size() {
+        let map = DeviceMap::builder()
+            .button(GamepadButton::South)
+            .bit(7, 0)
+            .build()
+            .unwrap();
+        assert!(map.validate_report_size(8).is_valid());
+        let errors = map.validate_report_size(4).errors;
+        assert_eq!(
+            errors[0].issue,
+            ValidationError::OutOfRange { index: 7, size: 4 }
+        );
+
+        let chord = crate::device::Chord {
+            buttons: Vec::new(),
+            window: None,
+            suppress: false,
+        };
+        let map = map.with_chords([(GamepadButton::Other(1), chord)].into());
+        assert_eq!(map.validate().errors[0].issue, ValidationError::EmptyChord);
+
+        let inverted = DeviceMatch::Range {
+            vid: 1,
+            min: 9,
+            max: 2,
+        };
+        let device =
+            DeviceAsset::new("Pad".to_string(), 1, 2, DeviceMap::default()).with_id(inverted);
+        assert_eq!(
+            device.validate_report_size(REPORT_SIZE).errors[0].issue,
+            ValidationError::InvertedRange { min: 9, max: 2 }
+        );
+    }
+}